@@ -0,0 +1,60 @@
+//! Events to mark on the chart, read from a file.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono_tz::Tz;
+use eth_hist_plot::chart::Annotation;
+use eth_hist_plot::Error;
+use serde::Deserialize;
+
+/// An entry as written in the file, the date is checked afterwards to tell
+/// which entry is invalid.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    date: String,
+    label: String,
+}
+
+/// The top level of a TOML file has to be a table, so the events are listed
+/// as `[[annotations]]`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Toml {
+    #[serde(default)]
+    annotations: Vec<Entry>,
+}
+
+/// Reads the annotations at `path`, TOML if it ends in `.toml`, else a JSON
+/// array. Dates are RFC 3339 or `YYYY-MM-DD`, the start of the day in
+/// `timezone`.
+pub fn load(path: &Path, timezone: Tz) -> Result<Vec<Annotation>, Error> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        io::Error::new(err.kind(), format!("cannot read {}: {err}", path.display()))
+    })?;
+    let invalid =
+        |err: &dyn std::fmt::Display| Error::InvalidArgs(format!("{}: {err}", path.display()));
+
+    let entries = if path
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        toml::from_str::<Toml>(&content)
+            .map_err(|err| invalid(&err))?
+            .annotations
+    } else {
+        serde_json::from_str(&content).map_err(|err| invalid(&err))?
+    };
+
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, Entry { date, label })| {
+            let when = crate::parse_date_bound_in(&date, false, timezone)
+                .map_err(|err| invalid(&format!("entry {}: {err}", i + 1)))?;
+            Ok(Annotation { when, label })
+        })
+        .collect()
+}
@@ -0,0 +1,236 @@
+//! The axes of the panels, the time axis with its grid lines and labels at
+//! calendar boundaries and the logarithmic one at round values.
+
+use std::ops::Range;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use plotters::coord::combinators::{IntoLogRange, LogCoord};
+use plotters::coord::ranged1d::{
+    DefaultFormatting, KeyPointHint, NoDefaultFormatting, Ranged, ValueFormatter,
+};
+use plotters::coord::types::{RangedCoordf64, RangedDateTime};
+
+/// Dates on a range of time, ticked at the first of months or at the
+/// Mondays starting ISO weeks, whichever are the finest to fit, with lighter
+/// lines at the next finer unit between them. Windows too short for three
+/// Mondays are ticked as plotters does. Months, weeks and days start at
+/// midnight in the time zone the dates are labeled in.
+pub struct TimeAxis {
+    range: RangedDateTime<DateTime<Utc>>,
+    timezone: Tz,
+}
+
+impl TimeAxis {
+    pub fn new(range: Range<DateTime<Utc>>, timezone: Tz) -> Self {
+        Self {
+            range: range.into(),
+            timezone,
+        }
+    }
+}
+
+/// A unit of time ticks are spaced by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Days,
+    Weeks(u32),
+    Months(u32),
+}
+
+impl Step {
+    /// From the finest to the coarsest, for the major ticks.
+    const MAJOR: [Self; 10] = [
+        Self::Weeks(1),
+        Self::Weeks(2),
+        Self::Months(1),
+        Self::Months(2),
+        Self::Months(3),
+        Self::Months(6),
+        Self::Months(12),
+        Self::Months(24),
+        Self::Months(60),
+        Self::Months(120),
+    ];
+
+    /// The step of the minor ticks between major ticks of `self`.
+    fn minor(self) -> Self {
+        match self {
+            Self::Days | Self::Weeks(1) => Self::Days,
+            Self::Weeks(_) | Self::Months(1) => Self::Weeks(1),
+            Self::Months(_) => Self::Months(1),
+        }
+    }
+
+    /// The ticks within `range`, at midnight in `timezone`. Days starting
+    /// later, e.g. for daylight saving time, get no tick.
+    fn ticks(self, range: &Range<DateTime<Utc>>, timezone: Tz) -> Vec<DateTime<Utc>> {
+        let midnight = |date: NaiveDate| {
+            timezone
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map(|midnight| midnight.with_timezone(&Utc))
+        };
+        let mut date = range.start.with_timezone(&timezone).date_naive();
+        if midnight(date).is_none_or(|midnight| midnight < range.start) {
+            date = date.succ_opt().unwrap_or(date);
+        }
+        let dates: Box<dyn Iterator<Item = NaiveDate>> = match self {
+            Self::Days => Box::new(date.iter_days()),
+            Self::Weeks(weeks) => {
+                // aligned to the Monday starting the first ISO week of 1970
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 5).expect("valid date");
+                let days = i64::from(weeks) * 7;
+                let offset = (date - epoch).num_days().rem_euclid(days);
+                let first = if offset == 0 {
+                    date
+                } else {
+                    date + TimeDelta::days(days - offset)
+                };
+                Box::new(first.iter_weeks().step_by(weeks as usize))
+            }
+            Self::Months(months) => {
+                let first_of_month = date.with_day(1).expect("valid date");
+                let mut first = if first_of_month < date {
+                    first_of_month + Months::new(1)
+                } else {
+                    first_of_month
+                };
+                // aligned to January of years divisible by the step
+                let index = first.year() * 12 + i32::try_from(first.month0()).expect("a month");
+                let offset = index.rem_euclid(i32::try_from(months).expect("a few months"));
+                if offset != 0 {
+                    first = first + Months::new(months - offset.unsigned_abs());
+                }
+                Box::new(std::iter::successors(Some(first), move |date| {
+                    date.checked_add_months(Months::new(months))
+                }))
+            }
+        };
+        dates
+            .filter_map(midnight)
+            .take_while(|tick| *tick <= range.end)
+            .collect()
+    }
+}
+
+impl Ranged for TimeAxis {
+    type FormatOption = DefaultFormatting;
+    type ValueType = DateTime<Utc>;
+
+    fn map(&self, value: &DateTime<Utc>, limit: (i32, i32)) -> i32 {
+        self.range.map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<DateTime<Utc>> {
+        let range = self.range.range();
+        let bold = hint.bold_points();
+        let Some((step, mut ticks)) = Step::MAJOR.iter().find_map(|&step| {
+            let ticks = step.ticks(&range, self.timezone);
+            (ticks.len() <= bold).then_some((step, ticks))
+        }) else {
+            return self.range.key_points(hint);
+        };
+        if ticks.len() < 3 {
+            return self.range.key_points(hint);
+        }
+
+        if hint.weight().allow_light_points() {
+            let minor = step.minor().ticks(&range, self.timezone);
+            if minor.len() <= hint.max_num_points() {
+                ticks.extend(minor);
+                ticks.sort_unstable();
+                ticks.dedup();
+            }
+        }
+        ticks
+    }
+
+    fn range(&self) -> Range<DateTime<Utc>> {
+        self.range.range()
+    }
+}
+
+/// Positive values on a logarithmic scale, ticked at powers of ten, or at
+/// one, two and five times them if less than three powers fit, with lighter
+/// lines at the multiples between them. Ranges too short for three such
+/// ticks are ticked linearly.
+pub struct LogAxis(LogCoord<f64>);
+
+impl LogAxis {
+    pub fn new(range: Range<f64>) -> Self {
+        Self(range.log_scale().into())
+    }
+}
+
+/// The multiples of powers of ten by `mantissas` within `range`.
+fn decade_ticks(range: &Range<f64>, mantissas: &[f64]) -> Vec<f64> {
+    // exponents of f64 are far within i32
+    #[allow(clippy::cast_possible_truncation)]
+    let (first, last) = (
+        range.start.log10().floor() as i32,
+        range.end.log10().ceil() as i32,
+    );
+    (first..=last)
+        .flat_map(|exponent| {
+            let power = 10_f64.powi(exponent);
+            mantissas.iter().map(move |mantissa| mantissa * power)
+        })
+        .filter(|tick| (range.start..=range.end).contains(tick))
+        .collect()
+}
+
+impl Ranged for LogAxis {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        self.0.map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        const ALL: [f64; 9] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let range = self.0.range();
+        let bold = hint.bold_points().max(1);
+        let (mut ticks, minor) = match decade_ticks(&range, &[1.0]) {
+            decades if decades.len() >= 3 => (decades, decade_ticks(&range, &[1.0, 2.0, 5.0])),
+            _ => (
+                decade_ticks(&range, &[1.0, 2.0, 5.0]),
+                decade_ticks(&range, &ALL),
+            ),
+        };
+        if ticks.len() < 3 {
+            return RangedCoordf64::from(range).key_points(hint);
+        }
+        // every other decade and so on, if there are too many to label
+        if ticks.len() > bold {
+            let step = ticks.len().div_ceil(bold);
+            ticks = ticks.into_iter().step_by(step).collect();
+        } else if hint.weight().allow_light_points() && minor.len() <= hint.max_num_points() {
+            ticks.extend(minor);
+            ticks.sort_unstable_by(f64::total_cmp);
+            ticks.dedup();
+        }
+        ticks
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.0.range()
+    }
+}
+
+impl ValueFormatter<f64> for LogAxis {
+    /// With as many decimals as it takes to tell the ticks apart, e.g. `200`
+    /// or `0.05`.
+    fn format(value: &f64) -> String {
+        // enough for the 2 significant digits of the linear ticks
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let decimals = (1.0 - value.abs().log10().floor()).clamp(0.0, 12.0) as usize;
+        let label = format!("{value:.decimals$}");
+        if label.contains('.') {
+            label.trim_end_matches('0').trim_end_matches('.').to_owned()
+        } else {
+            label
+        }
+    }
+}
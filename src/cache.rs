@@ -0,0 +1,165 @@
+//! On-disk cache of API responses.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use eth_hist_plot::Data;
+use serde::{Deserialize, Serialize};
+
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Whether what's missing must not be fetched.
+    offline: bool,
+}
+
+impl Cache {
+    /// Cache living in the platform's cache directory, if there is one.
+    pub fn in_default_dir(ttl: Duration) -> Option<Self> {
+        let dir = dirs::cache_dir()?.join(env!("CARGO_PKG_NAME"));
+        Some(Self {
+            dir,
+            ttl,
+            offline: false,
+        })
+    }
+
+    /// Makes the cache the only source of responses, entries of any age
+    /// included since they can't be refreshed anyway.
+    pub fn offline(self) -> Self {
+        Self {
+            ttl: Duration::MAX,
+            offline: true,
+            ..self
+        }
+    }
+
+    /// Keeps the responses of the API at `base_url` apart from those of the
+    /// default one, in a directory of their own named after the URL.
+    pub fn for_api(self, base_url: &str) -> Self {
+        let url = base_url
+            .split_once("://")
+            .map_or(base_url, |(_, rest)| rest);
+        let name: String = url
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        Self {
+            dir: self.dir.join("api").join(name),
+            ..self
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns the cached `market_chart` response for `key`, see
+    /// [`Cache::load_with`].
+    pub fn load(&self, key: &str) -> Option<Data> {
+        self.load_with(key, eth_hist_plot::parse)
+    }
+
+    /// Returns the cached response for `key`, parsed with `parse`, if it is
+    /// younger than the TTL. Corrupt entries are treated as missing, so they
+    /// get refetched.
+    pub fn load_with<T>(
+        &self,
+        key: &str,
+        parse: fn(io::BufReader<fs::File>) -> eth_hist_plot::Result<T>,
+    ) -> Option<T> {
+        let path = self.path(key);
+        let age = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age > self.ttl {
+            return None;
+        }
+
+        let file = fs::File::open(path).ok()?;
+        parse(io::BufReader::new(file)).ok()
+    }
+
+    /// When the response for `key` was stored, or confirmed to be unchanged,
+    /// if it is cached.
+    pub fn stored_at(&self, key: &str) -> Option<SystemTime> {
+        fs::metadata(self.path(key))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// Returns the response for `key` whatever its age, parsed with `parse`,
+    /// if it was stored with [`Validators`] to ask the API whether it
+    /// changed.
+    pub fn stale_with<T>(
+        &self,
+        key: &str,
+        parse: fn(io::BufReader<fs::File>) -> eth_hist_plot::Result<T>,
+    ) -> Option<(T, Validators)> {
+        let validators = fs::read(self.validators_path(key)).ok()?;
+        let validators = serde_json::from_slice(&validators).ok()?;
+        let file = fs::File::open(self.path(key)).ok()?;
+        let value = parse(io::BufReader::new(file)).ok()?;
+        Some((value, validators))
+    }
+
+    /// Stores `body` as `key` along with its `validators`, replacing those of
+    /// an earlier response even if there are none.
+    pub fn store(&self, key: &str, body: &[u8], validators: &Validators) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(key), body)?;
+        let path = self.validators_path(key);
+        if validators.is_empty() {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        } else {
+            fs::write(path, serde_json::to_vec(validators)?)
+        }
+    }
+
+    /// Makes the response for `key` as young as if just stored, for when the
+    /// API says it is unchanged.
+    pub fn touch(&self, key: &str) -> io::Result<()> {
+        fs::File::options()
+            .append(true)
+            .open(self.path(key))?
+            .set_modified(SystemTime::now())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    fn validators_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.validators.json"))
+    }
+}
+
+/// The `ETag` and `Last-Modified` headers of a response, which requests for
+/// it send back to be answered with 304 if it is unchanged.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Key of the response for `coin` in `currency` over `days`, at the given
+/// `interval` if not the default.
+pub fn key(coin: &str, currency: &str, days: &str, interval: Option<&str>) -> String {
+    match interval {
+        Some(interval) => format!("{coin}-{currency}-{days}-{interval}"),
+        None => format!("{coin}-{currency}-{days}"),
+    }
+}
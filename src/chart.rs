@@ -0,0 +1,1626 @@
+//! Drawing the charts, to SVG in memory or to any backend of plotters.
+
+use std::ops::Range;
+use std::slice::Iter;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use plotters::backend::{DrawingBackend, SVGBackend};
+use plotters::chart::ChartBuilder;
+use plotters::chart::{ChartContext, MeshStyle};
+use plotters::coord::cartesian::Cartesian2d;
+use plotters::coord::ranged1d::{Ranged, ValueFormatter};
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, IntoDrawingArea};
+use plotters::element::{CandleStick, Circle, EmptyElement, PathElement, Polygon, Rectangle, Text};
+use plotters::series::{AreaSeries, DashedLineSeries, LineSeries, PointSeries};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{
+    AsRelative, Color, FontDesc, FontFamily, FontStyle, FontTransform, RGBColor, TextStyle,
+};
+
+use crate::axis::{LogAxis, TimeAxis};
+use crate::days::Days;
+use crate::labels::{self, Formatter, LabelStyle};
+use crate::theme::{Palette, Theme};
+use crate::{downsample, BandPoints, Candle, Cross, Crossover, Data, Datum, Error, Histogram};
+
+/// How the prices are drawn.
+#[derive(Clone, Copy, Debug)]
+pub enum SeriesStyle {
+    Line,
+    /// Filled from the bottom of the panel up to the line.
+    Area,
+    /// A dot per value, not connected.
+    Scatter,
+    /// A bar per value from zero up, for the volume only, see
+    /// `--volume-style`.
+    Bar,
+}
+
+/// A panel of the chart, see `--metric`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Price,
+    MarketCap,
+    Volume,
+}
+
+impl Metric {
+    /// All panels, in the order they are drawn.
+    pub const ALL: [Self; 3] = [Self::Price, Self::MarketCap, Self::Volume];
+}
+
+pub type Bounds = (Range<DateTime<Utc>>, Range<f64>);
+
+/// Computes the time and value ranges spanned by `series`, failing if it
+/// doesn't contain a single value to plot.
+fn bounds<'a>(series: impl Iterator<Item = &'a Datum>, what: &str) -> Result<Bounds, Error> {
+    let mut bounds: Option<Bounds> = None;
+    for (timestamp, value) in series.filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+    {
+        let (x_range, y_range) = bounds.get_or_insert((timestamp..timestamp, value..value));
+        x_range.start = x_range.start.min(timestamp);
+        x_range.end = x_range.end.max(timestamp);
+        y_range.start = y_range.start.min(value);
+        y_range.end = y_range.end.max(value);
+    }
+
+    bounds.ok_or_else(|| {
+        Error::EmptyData(format!("no {what} data available for the requested range"))
+    })
+}
+
+/// How one of the panels of the price, the market cap and the volume is
+/// colored and captioned, as the palette and the coins have it if unset.
+#[derive(Clone, Debug, Default)]
+pub struct PanelStyle {
+    /// Of the line of the first coin, the others keep those of the palette.
+    pub color: Option<RGBColor>,
+    /// In place of the caption, e.g. `Ethereum market cap`.
+    pub label: Option<String>,
+}
+
+impl PanelStyle {
+    /// The caption of the panel of `what` for the coins named `name`.
+    fn caption(&self, name: &str, what: &str) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("{name} {what}"))
+    }
+
+    /// `palette` with the color of the panel, if set.
+    fn palette(&self, palette: &Palette) -> Palette {
+        let mut palette = *palette;
+        if let Some(color) = self.color {
+            palette.lines[0] = color;
+        }
+        palette
+    }
+}
+
+/// Of each of the panels with a series of the data.
+#[derive(Clone, Debug, Default)]
+pub struct PanelStyles {
+    pub price: PanelStyle,
+    pub market_cap: PanelStyle,
+    pub volume: PanelStyle,
+}
+
+/// How the mesh of the panels is drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct Grid {
+    /// Number of labels and grid lines along the x axis, as many as fit the
+    /// width if unset.
+    pub x_labels: Option<usize>,
+    /// Number of labels and grid lines along the y axis, as plotters picks
+    /// if unset.
+    pub y_labels: Option<usize>,
+    /// Whether grid lines are drawn, the labels are drawn regardless.
+    pub lines: bool,
+    /// Of the dates labeled on the x axis and of the days, weeks and months
+    /// they are placed at, the data itself stays in UTC.
+    pub timezone: Tz,
+}
+
+impl Grid {
+    /// Applies what's set to `mesh`, whatever its coordinates.
+    fn configure<DB, X, Y, XT, YT>(self, mesh: &mut MeshStyle<'_, '_, X, Y, DB>)
+    where
+        DB: DrawingBackend,
+        X: Ranged<ValueType = XT> + ValueFormatter<XT>,
+        Y: Ranged<ValueType = YT> + ValueFormatter<YT>,
+    {
+        if let Some(x_labels) = self.x_labels {
+            mesh.x_labels(x_labels);
+        }
+        if let Some(y_labels) = self.y_labels {
+            mesh.y_labels(y_labels);
+        }
+        if !self.lines {
+            mesh.disable_mesh();
+        }
+    }
+}
+
+/// The typeface of all text on the chart.
+#[derive(Clone, Debug)]
+pub struct Font {
+    pub family: String,
+    /// Multiplies the sizes of all text.
+    pub scale: f64,
+}
+
+impl Font {
+    fn family(&self) -> FontFamily<'_> {
+        FontFamily::from(self.family.as_str())
+    }
+
+    /// The font at `size` pixels, scaled.
+    fn sized(&self, size: impl Into<f64>) -> FontDesc<'_> {
+        FontDesc::new(self.family(), size.into() * self.scale, FontStyle::Normal)
+    }
+
+    /// `size` pixels scaled, for bounds of sizes relative to an area.
+    // text is nowhere near i32::MAX pixels
+    #[allow(clippy::cast_possible_truncation)]
+    fn pixels(&self, size: i32) -> i32 {
+        (f64::from(size) * self.scale).round() as i32
+    }
+}
+
+/// How the series are turned into lines.
+#[derive(Clone, Copy, Debug)]
+pub struct LineOptions {
+    /// Whether lines break at missing values instead of connecting the
+    /// values around them.
+    pub broken: bool,
+    /// Downsample series with more points than this.
+    pub max_points: Option<usize>,
+}
+
+/// Collects the values of `series` into the runs between missing values if
+/// lines are `broken`, else into a single run skipping them. If there are
+/// more than `max_points` values, each run is downsampled to its share.
+fn segments(series: Iter<'_, Datum>, options: LineOptions) -> Vec<Vec<(DateTime<Utc>, f64)>> {
+    let mut segments = vec![Vec::new()];
+    for datum in series {
+        match datum.price() {
+            Some(value) => segments
+                .last_mut()
+                .expect("never empty")
+                .push((*datum.timestamp(), value)),
+            None if options.broken && !segments.last().expect("never empty").is_empty() => {
+                segments.push(Vec::new());
+            }
+            None => {}
+        }
+    }
+
+    let total: usize = segments.iter().map(Vec::len).sum();
+    match options.max_points {
+        Some(max_points) if total > max_points => segments
+            .iter()
+            .map(|segment| downsample(segment, (segment.len() * max_points / total).max(3)))
+            .collect(),
+        _ => segments,
+    }
+}
+
+/// Finds the value of `series` whose timestamp is closest to `when`.
+fn nearest_value(series: Iter<'_, Datum>, when: DateTime<Utc>) -> Option<f64> {
+    series
+        .filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+        .min_by_key(|(timestamp, _)| (*timestamp - when).abs())
+        .map(|(_, price)| price)
+}
+
+/// Data of one of the coins to plot.
+pub struct Coin {
+    pub name: String,
+    pub data: Data,
+}
+
+/// A point in time marked on every panel.
+pub struct Annotation {
+    pub when: DateTime<Utc>,
+    pub label: String,
+}
+
+/// An interval shaded over the whole height of a panel.
+pub struct Span {
+    pub range: Range<DateTime<Utc>>,
+    pub color: RGBColor,
+    pub label: String,
+}
+
+/// A labeled point drawn on top of a panel.
+pub struct Marker {
+    pub point: (DateTime<Utc>, f64),
+    pub color: RGBColor,
+    pub label: String,
+}
+
+/// The change of the price over the whole chart, written in a corner of the
+/// price panel.
+pub struct Change {
+    pub label: String,
+    pub color: RGBColor,
+}
+
+/// An additional line drawn on top of a panel.
+#[derive(Clone)]
+pub struct Overlay {
+    pub points: Vec<(DateTime<Utc>, f64)>,
+    pub color: RGBColor,
+    pub label: String,
+    pub dashed: bool,
+}
+
+/// A shaded area between a lower and an upper line.
+pub struct Band {
+    pub points: BandPoints,
+    pub color: RGBColor,
+    pub label: String,
+}
+
+/// Everything drawn on the panels besides the series themselves.
+#[derive(Default)]
+pub struct Decorations {
+    pub annotation: Option<Annotation>,
+    /// Drawn on the price panel.
+    pub markers: Vec<Marker>,
+    /// Shaded on the price panel.
+    pub spans: Vec<Span>,
+    /// Shaded faintly on the price panel, behind the spans.
+    pub weekends: Vec<Range<DateTime<Utc>>>,
+    /// Drawn as vertical lines across the price panel.
+    pub vlines: Vec<Annotation>,
+    pub price_overlays: Vec<Overlay>,
+    pub price_band: Option<Band>,
+    /// Of the moving averages among the overlays.
+    pub crossovers: Vec<Crossover>,
+    pub change: Option<Change>,
+    /// Drawn on a panel of its own below the prices.
+    pub rsi: Option<Overlay>,
+    /// Of the daily returns in percent, drawn on a panel of its own below
+    /// the RSI.
+    pub returns: Option<Histogram>,
+}
+
+/// Ranges of the panels to plot, see [`bounds`].
+pub struct Ranges {
+    pub price: Bounds,
+    /// Missing if there are no market caps at all, e.g. from Binance.
+    pub market_cap: Option<Bounds>,
+    /// Missing if not requested or there are no volumes at all.
+    pub volume: Option<Bounds>,
+}
+
+type DateChart<'a, DB, Y> = ChartContext<'a, DB, Cartesian2d<TimeAxis, Y>>;
+
+impl Ranges {
+    /// Ranges spanning the data of all `coins` and the `candles`, the price
+    /// always as other panels share its time, market caps and volumes if
+    /// among `metrics`.
+    ///
+    /// # Errors
+    ///
+    /// If there isn't a single price to plot.
+    pub fn new(
+        coins: &[Coin],
+        candles: Option<&[Candle]>,
+        metrics: &[Metric],
+    ) -> Result<Self, Error> {
+        let mut ranges = Self {
+            price: bounds(
+                coins.iter().flat_map(|coin| coin.data.iter_prices()),
+                "price",
+            )?,
+            market_cap: if metrics.contains(&Metric::MarketCap)
+                && coins
+                    .iter()
+                    .flat_map(|coin| coin.data.iter_market_caps())
+                    .any(|datum| datum.price().is_some())
+            {
+                Some(bounds(
+                    coins.iter().flat_map(|coin| coin.data.iter_market_caps()),
+                    "market cap",
+                )?)
+            } else {
+                None
+            },
+            volume: if metrics.contains(&Metric::Volume)
+                && coins
+                    .iter()
+                    .flat_map(|coin| coin.data.iter_total_volumes())
+                    .any(|datum| datum.price().is_some())
+            {
+                Some(bounds(
+                    coins.iter().flat_map(|coin| coin.data.iter_total_volumes()),
+                    "volume",
+                )?)
+            } else {
+                None
+            },
+        };
+        if let Some(candles) = candles {
+            ranges.include_candles(candles);
+        }
+        Ok(ranges)
+    }
+
+    /// Extends the price range to the highs and lows of `candles`.
+    fn include_candles(&mut self, candles: &[Candle]) {
+        let (x_range, y_range) = &mut self.price;
+        for candle in candles {
+            x_range.start = x_range.start.min(candle.timestamp);
+            x_range.end = x_range.end.max(candle.timestamp);
+            y_range.start = y_range.start.min(candle.low);
+            y_range.end = y_range.end.max(candle.high);
+        }
+    }
+}
+
+/// Everything needed to draw the panels.
+pub struct Chart {
+    pub coins: Vec<Coin>,
+    pub ranges: Ranges,
+    /// Fetched, unknown for data read from a file.
+    pub days: Option<Days>,
+    pub decorations: Decorations,
+    /// Drawn instead of the prices of the single coin if fetched.
+    pub candles: Option<Vec<Candle>>,
+    pub options: PlotOptions,
+}
+
+impl Chart {
+    /// Plots the chart as SVG of the size of its options.
+    ///
+    /// # Errors
+    ///
+    /// If none of the panels has data to plot or plotters fails to draw it.
+    pub fn svg(&self) -> Result<String, Error> {
+        let mut svg = String::new();
+        let root = SVGBackend::with_string(&mut svg, self.options.size).into_drawing_area();
+        // the light theme keeps the background transparent
+        if let Theme::Dark = self.options.theme {
+            root.fill(&self.options.palette.background)?;
+        }
+        plot(&root, self)?;
+        root.present()?;
+        drop(root);
+        Ok(svg)
+    }
+
+    /// Number of panels to plot.
+    #[must_use]
+    pub fn panels(&self) -> usize {
+        usize::from(self.options.metrics.contains(&Metric::Price))
+            + usize::from(self.decorations.rsi.is_some())
+            + usize::from(self.decorations.returns.is_some())
+            + usize::from(self.ranges.market_cap.is_some())
+            + usize::from(self.ranges.volume.is_some())
+    }
+
+    /// What the chart shows, e.g. `Ethereum in USD, last 30 days`, unless
+    /// given a title.
+    #[must_use]
+    pub fn title(&self) -> String {
+        if let Some(title) = &self.options.title {
+            return title.clone();
+        }
+        let span = match self.days {
+            Some(Days::Count(1)) => ", last day".to_owned(),
+            Some(Days::Count(days)) => format!(", last {days} days"),
+            Some(Days::Max) => ", all time".to_owned(),
+            None => String::new(),
+        };
+        format!("{} in {}{span}", self.name(), self.options.currency)
+    }
+
+    /// What of the panels is drawn on a linear y axis despite the log scale,
+    /// which can't show the non-positive values among theirs.
+    #[must_use]
+    pub fn linear_panels(&self) -> Vec<&'static str> {
+        if !self.options.log_scale {
+            return Vec::new();
+        }
+        let price = self
+            .options
+            .metrics
+            .contains(&Metric::Price)
+            .then_some(("price", &self.ranges.price));
+        let market_cap = self
+            .ranges
+            .market_cap
+            .as_ref()
+            .map(|bounds| ("market cap", bounds));
+        [price, market_cap]
+            .into_iter()
+            .flatten()
+            .filter(|(_, (_, y_range))| y_range.start <= 0.0)
+            .map(|(what, _)| what)
+            .collect()
+    }
+
+    /// Names of all coins, as they appear in the captions.
+    #[must_use]
+    pub fn name(&self) -> String {
+        let names: Vec<_> = self.coins.iter().map(|coin| coin.name.as_str()).collect();
+        names.join(" vs ")
+    }
+
+    /// The price panel with all its decorations in the colors of `palette`,
+    /// the prices formatted with `price_labels` and the volume, if drawn on
+    /// it, with `value_labels`.
+    fn price_panel<'c>(
+        &'c self,
+        price_labels: Option<&'c Formatter>,
+        value_labels: Option<&'c Formatter>,
+        palette: &'c Palette,
+    ) -> Panel<'c> {
+        let in_currency = format!("in {}", self.options.currency);
+        let (y_desc, unit) = if self.options.normalized {
+            ("% change", "% change")
+        } else {
+            (self.options.currency.as_str(), in_currency.as_str())
+        };
+        let decorations = &self.decorations;
+        Panel {
+            lines: self.lines(Data::iter_prices, "price", unit),
+            style: self.options.series_style,
+            candles: self.candles.as_deref(),
+            line_options: self.options.line_options,
+            y_desc,
+            annotation: decorations.annotation.as_ref(),
+            markers: &decorations.markers,
+            spans: &decorations.spans,
+            weekends: &decorations.weekends,
+            vlines: &decorations.vlines,
+            overlays: &decorations.price_overlays,
+            band: decorations.price_band.as_ref(),
+            crossovers: &decorations.crossovers,
+            change: decorations.change.as_ref(),
+            y_labels: price_labels,
+            volume: self.options.volume_overlay.then(|| VolumeBars {
+                y_desc: format!("{} volume {in_currency}", self.coins[0].name),
+                series: self.coins[0].data.iter_total_volumes(),
+                y_labels: value_labels,
+            }),
+            palette,
+            grid: self.options.grid,
+            font: &self.options.font,
+        }
+    }
+
+    /// One labeled line per coin, of the values of `what` returned by
+    /// `series`.
+    fn lines(
+        &self,
+        series: fn(&Data) -> Iter<'_, Datum>,
+        what: &str,
+        unit: &str,
+    ) -> Vec<(String, Iter<'_, Datum>)> {
+        self.coins
+            .iter()
+            .map(|coin| (format!("{} {what} {unit}", coin.name), series(&coin.data)))
+            .collect()
+    }
+}
+
+/// Plots all panels of `chart` on `root`, with its background left as it is.
+///
+/// # Errors
+///
+/// If none of the panels has data to plot or plotters fails to draw on
+/// `root`.
+///
+/// # Panics
+///
+/// Never, `root` is split into an area for each of the panels.
+pub fn plot<DB>(root: &DrawingArea<DB, Shift>, chart: &Chart) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let Chart {
+        coins,
+        ranges,
+        // see Chart::title
+        days: _,
+        decorations,
+        // see Chart::price_panel
+        candles: _,
+        options,
+    } = chart;
+    let PlotOptions {
+        // see render_svg
+        name: _,
+        currency,
+        // of the backend
+        size: _,
+        title,
+        log_scale,
+        normalized,
+        // see Chart::svg
+        theme: _,
+        palette,
+        watermark,
+        // see draw_price_panel
+        volume_overlay: _,
+        metrics,
+        label_styles: (price_style, value_style),
+        // see Chart::price_panel
+        series_style: _,
+        volume_style,
+        panel_styles,
+        line_options,
+        grid,
+        font,
+    } = options;
+    let panels = chart.panels();
+    if panels == 0 {
+        return Err(Error::EmptyData("no panel has data to plot".to_owned()));
+    }
+    let root = draw_header(root, title.as_deref(), watermark.as_deref(), palette, font)?;
+    let sub_roots = root.split_evenly((panels, 1));
+    let mut sub_roots = sub_roots.iter();
+    let mut next_area = || sub_roots.next().expect("one area per panel");
+    let (_, height) = root.dim_in_pixel();
+    let caption = font
+        .sized(caption_size(height, panels))
+        .color(&palette.foreground);
+
+    let price_labels = if *normalized {
+        Some(labels::percent_change())
+    } else {
+        labels::formatter(*price_style, Some(currency))
+    };
+    let value_labels = labels::formatter(*value_style, Some(currency));
+
+    if metrics.contains(&Metric::Price) {
+        draw_price_panel(
+            (next_area(), &caption),
+            chart,
+            price_labels.as_deref(),
+            value_labels.as_deref(),
+        )?;
+    }
+
+    if let Some(rsi) = &decorations.rsi {
+        let builder = panel_builder(
+            next_area(),
+            (format!("{} RSI", coins[0].name), &caption),
+            10,
+        );
+        draw_rsi(
+            builder,
+            ranges.price.0.clone(),
+            rsi,
+            (palette, *grid, font),
+            *line_options,
+        )?;
+    }
+
+    if let Some(returns) = &decorations.returns {
+        let builder = panel_builder(
+            next_area(),
+            (format!("{} daily returns", coins[0].name), &caption),
+            10,
+        );
+        draw_histogram(builder, returns, (palette, *grid, font))?;
+    }
+
+    if let Some(bounds) = ranges.market_cap.clone() {
+        draw_line_panel(
+            (next_area(), &caption),
+            chart,
+            (Data::iter_market_caps, "market cap"),
+            &panel_styles.market_cap,
+            SeriesStyle::Line,
+            (bounds, *log_scale),
+            value_labels.as_deref(),
+        )?;
+    }
+
+    if let Some(bounds) = ranges.volume.clone() {
+        draw_line_panel(
+            (next_area(), &caption),
+            chart,
+            (Data::iter_total_volumes, "volume"),
+            &panel_styles.volume,
+            *volume_style,
+            (bounds, false),
+            value_labels.as_deref(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Draws the price panel of `chart` on `area`, labeled with `price_labels`
+/// and the volume, if drawn on it, with `value_labels`.
+fn draw_price_panel<DB>(
+    (area, caption): (&DrawingArea<DB, Shift>, &TextStyle<'_>),
+    chart: &Chart,
+    price_labels: Option<&Formatter>,
+    value_labels: Option<&Formatter>,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let style = &chart.options.panel_styles.price;
+    let mut builder = panel_builder(area, (style.caption(&chart.name(), "price"), caption), 10);
+    widen_labels(&mut builder, price_labels);
+    if chart.options.volume_overlay {
+        builder.right_y_label_area_size(80);
+    }
+
+    let palette = style.palette(&chart.options.palette);
+    let price = chart.price_panel(price_labels, value_labels, &palette);
+    let bounds = chart.ranges.price.clone();
+    draw_scaled(&mut builder, bounds, chart.options.log_scale, price)
+}
+
+/// Draws the `watermark` in the top right corner of `root` and the `title`
+/// centered above the panels, returning the area left for them.
+fn draw_header<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: Option<&str>,
+    watermark: Option<&str>,
+    palette: &Palette,
+    font: &Font,
+) -> Result<DrawingArea<DB, Shift>, Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    if let Some(watermark) = watermark {
+        // in the margin above the top panel, clear of its caption
+        let (width, _) = root.dim_in_pixel();
+        let style = font
+            .sized(10)
+            .color(&palette.band)
+            .pos(Pos::new(HPos::Right, VPos::Top));
+        root.draw(&Text::new(
+            watermark,
+            (i32::try_from(width).unwrap_or(i32::MAX) - 4, 2),
+            style,
+        ))?;
+    }
+    Ok(match title {
+        Some(title) => {
+            let (_, height) = root.dim_in_pixel();
+            let style = font
+                .sized(caption_size(height, 1))
+                .style(FontStyle::Bold)
+                .color(&palette.foreground);
+            root.titled(title, style)?
+        }
+        None => root.clone(),
+    })
+}
+
+/// Draws the amounts of `what` returned by `series` for all coins in `style`
+/// on a panel of its own on `area`, colored and captioned as `panel_style`
+/// says, within `bounds` and with a logarithmic y axis if requested and
+/// usable.
+fn draw_line_panel<DB>(
+    (area, caption): (&DrawingArea<DB, Shift>, &TextStyle<'_>),
+    chart: &Chart,
+    (series, what): (fn(&Data) -> Iter<'_, Datum>, &str),
+    panel_style: &PanelStyle,
+    style: SeriesStyle,
+    (bounds, log_scale): (Bounds, bool),
+    y_labels: Option<&Formatter>,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let mut builder = panel_builder(
+        area,
+        (panel_style.caption(&chart.name(), what), caption),
+        55,
+    );
+    widen_labels(&mut builder, y_labels);
+
+    let in_currency = format!("in {}", chart.options.currency);
+    let palette = panel_style.palette(&chart.options.palette);
+    let panel = Panel {
+        style,
+        ..Panel::new(
+            chart.lines(series, what, &in_currency),
+            chart.options.line_options,
+            &chart.options.currency,
+            y_labels,
+            chart.decorations.annotation.as_ref(),
+            (&palette, chart.options.grid, &chart.options.font),
+        )
+    };
+    draw_scaled(&mut builder, bounds, log_scale, panel)
+}
+
+/// Size of the captions of `panels` panels sharing `height` pixels. They were
+/// sized 50 for two panels on 1024×768 and shrink with the height and once
+/// panels get lower than 256 pixels, the margins and label areas hold text of
+/// a fixed size and stay as they are.
+fn caption_size(height: u32, panels: usize) -> u32 {
+    let panel_height = height / u32::try_from(panels).expect("a few panels");
+    (50 * height / 768).min(50 * panel_height / 256).max(15)
+}
+
+/// Starts a panel on `area` with the given caption and left margin, the
+/// other margins and the label areas are the same for all panels.
+fn panel_builder<'a, 'b, DB: DrawingBackend>(
+    area: &'a DrawingArea<DB, Shift>,
+    (caption, style): (String, &TextStyle<'b>),
+    margin_left: u32,
+) -> ChartBuilder<'a, 'b, DB> {
+    let mut builder = ChartBuilder::on(area);
+    builder
+        .caption(caption, style.clone())
+        .margin(10)
+        .margin_left(margin_left)
+        .x_label_area_size(30)
+        .y_label_area_size(30);
+    builder
+}
+
+/// Makes room for the labels of the y axis if `y_labels` formats them, they
+/// are wider than the ones of plotters.
+fn widen_labels<DB: DrawingBackend>(
+    builder: &mut ChartBuilder<'_, '_, DB>,
+    y_labels: Option<&Formatter>,
+) {
+    if y_labels.is_some() {
+        builder.y_label_area_size(60);
+    }
+}
+
+/// Draws the `rsi` on a scale from 0 to 100, with reference lines at the
+/// usual thresholds of 30 and 70.
+fn draw_rsi<DB>(
+    mut builder: ChartBuilder<'_, '_, DB>,
+    x_range: Range<DateTime<Utc>>,
+    rsi: &Overlay,
+    (palette, grid, font): (&Palette, Grid, &Font),
+    line_options: LineOptions,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let mut chart =
+        builder.build_cartesian_2d(TimeAxis::new(x_range.clone(), grid.timezone), 0.0..100.0)?;
+
+    let reference = |level: f64, label: &str| Overlay {
+        points: vec![(x_range.start, level), (x_range.end, level)],
+        color: palette.band,
+        label: label.to_owned(),
+        dashed: true,
+    };
+    let overlays = [
+        rsi.clone(),
+        reference(70.0, "overbought (70)"),
+        reference(30.0, "oversold (30)"),
+    ];
+    let panel = Panel {
+        overlays: &overlays,
+        ..Panel::new(
+            Vec::new(),
+            line_options,
+            "RSI",
+            None,
+            None,
+            (palette, grid, font),
+        )
+    };
+    draw_line(&mut chart, panel)
+}
+
+/// Draws the bars of `histogram` of returns in percent, the x axis centered
+/// on no change.
+// counts of days are exact in f64
+#[allow(clippy::cast_precision_loss)]
+fn draw_histogram<DB>(
+    mut builder: ChartBuilder<'_, '_, DB>,
+    histogram: &Histogram,
+    (palette, grid, font): (&Palette, Grid, &Font),
+) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let max = histogram.counts.iter().copied().max().unwrap_or_default();
+    let mut chart = builder.build_cartesian_2d(histogram.range.clone(), 0.0..max as f64)?;
+
+    let foreground = &palette.foreground;
+    let mut mesh = chart.configure_mesh();
+    mesh.x_desc("return in %")
+        .y_desc("days")
+        .bold_line_style(foreground.mix(0.2))
+        .light_line_style(foreground.mix(0.1))
+        .axis_style(foreground)
+        .label_style(font.sized(12).color(foreground))
+        .x_label_formatter(&|value| format!("{value:+.1}"))
+        .y_label_formatter(&|value| format!("{value:.0}"));
+    grid.configure(&mut mesh);
+    mesh.draw()?;
+
+    let width = histogram.bin_width();
+    let style = palette.lines[0].mix(0.6).filled();
+    chart.draw_series(histogram.counts.iter().enumerate().map(|(bin, &count)| {
+        let start = histogram.range.start + bin as f64 * width;
+        Rectangle::new([(start, 0.0), (start + width, count as f64)], style)
+    }))?;
+    Ok(())
+}
+
+/// Builds the chart of `panel` within `bounds`, padded so that the lines
+/// don't run along the borders, with a logarithmic y axis if `log_scale` is
+/// requested and usable, see [`Chart::linear_panels`].
+fn draw_scaled<DB>(
+    builder: &mut ChartBuilder<'_, '_, DB>,
+    bounds: Bounds,
+    log_scale: bool,
+    panel: Panel<'_>,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (x_range, y_range) = bounds;
+    if log_scale && y_range.start > 0.0 {
+        // by the same share of the decades
+        let (start, end) = (y_range.start.log10(), y_range.end.log10());
+        let padding = Y_PADDING * (end - start);
+        let y_range = 10_f64.powf(start - padding)..10_f64.powf(end + padding);
+        let chart = builder.build_cartesian_2d(
+            TimeAxis::new(x_range, panel.grid.timezone),
+            LogAxis::new(y_range),
+        )?;
+        draw_with_volume(chart, panel)
+    } else {
+        let chart = builder.build_cartesian_2d(
+            TimeAxis::new(x_range, panel.grid.timezone),
+            padded(&y_range),
+        )?;
+        draw_with_volume(chart, panel)
+    }
+}
+
+/// Share of the range of the values left free above and below them.
+const Y_PADDING: f64 = 0.05;
+
+/// Widens `y_range` by [`Y_PADDING`] on both ends, though not below zero if
+/// it doesn't reach below, where prices and volumes can't go.
+fn padded(y_range: &Range<f64>) -> Range<f64> {
+    let padding = Y_PADDING * (y_range.end - y_range.start);
+    let start = if y_range.start >= 0.0 {
+        (y_range.start - padding).max(0.0)
+    } else {
+        y_range.start - padding
+    };
+    start..y_range.end + padding
+}
+
+/// Draws `panel` on `chart`, with its volume as bars behind the lines on a
+/// secondary axis to the right if there is one.
+fn draw_with_volume<'a, DB, Y>(
+    mut chart: DateChart<'a, DB, Y>,
+    mut panel: Panel<'_>,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let Some(VolumeBars {
+        y_desc,
+        series,
+        y_labels,
+    }) = panel.volume.take()
+    else {
+        return draw_line(&mut chart, panel);
+    };
+
+    let volume: Vec<_> = series
+        .filter_map(|x| x.price().map(|value| (*x.timestamp(), value)))
+        .collect();
+    let max = volume.iter().map(|&(_, value)| value).fold(0.0, f64::max);
+    let x_range = chart.x_range();
+    let mut chart = chart.set_secondary_coord(
+        TimeAxis::new(x_range, panel.grid.timezone),
+        0.0..max.max(f64::MIN_POSITIVE),
+    );
+
+    let foreground = &panel.palette.foreground;
+    let mut axes = chart.configure_secondary_axes();
+    axes.y_desc(y_desc)
+        .axis_style(foreground)
+        .label_style(panel.font.sized(12).color(foreground));
+    if let Some(y_labels) = y_labels {
+        axes.y_label_formatter(y_labels);
+    }
+    axes.draw()?;
+    // each bar reaches to the next timestamp
+    let style = panel.palette.band.mix(0.3).filled();
+    chart.draw_secondary_series(
+        volume
+            .windows(2)
+            .map(|pair| Rectangle::new([(pair[0].0, 0.0), (pair[1].0, pair[0].1)], style)),
+    )?;
+
+    draw_line(&mut chart, panel)
+}
+
+/// What [`draw_line`] draws on a panel.
+struct Panel<'p> {
+    /// One line per labeled series.
+    lines: Vec<(String, Iter<'p, Datum>)>,
+    style: SeriesStyle,
+    /// Drawn instead of the lines, labeled as the first one.
+    candles: Option<&'p [Candle]>,
+    line_options: LineOptions,
+    y_desc: &'p str,
+    /// Marked on the value of the first series nearest to it.
+    annotation: Option<&'p Annotation>,
+    markers: &'p [Marker],
+    /// Shaded beneath the lines.
+    spans: &'p [Span],
+    /// Shaded faintly beneath the spans.
+    weekends: &'p [Range<DateTime<Utc>>],
+    /// Vertical lines spanning the whole panel.
+    vlines: &'p [Annotation],
+    overlays: &'p [Overlay],
+    /// Drawn beneath the lines.
+    band: Option<&'p Band>,
+    /// Marked on top of the overlays.
+    crossovers: &'p [Crossover],
+    /// Written in the top left corner.
+    change: Option<&'p Change>,
+    /// Formats the values on the y axis, as plotters does if unset.
+    y_labels: Option<&'p Formatter>,
+    /// Drawn behind everything else.
+    volume: Option<VolumeBars<'p>>,
+    palette: &'p Palette,
+    grid: Grid,
+    font: &'p Font,
+}
+
+/// Volume drawn as bars on a secondary axis, see [`draw_with_volume`].
+struct VolumeBars<'p> {
+    y_desc: String,
+    series: Iter<'p, Datum>,
+    y_labels: Option<&'p Formatter>,
+}
+
+impl<'p> Panel<'p> {
+    /// A panel with just the `lines` and the `annotation`.
+    fn new(
+        lines: Vec<(String, Iter<'p, Datum>)>,
+        line_options: LineOptions,
+        y_desc: &'p str,
+        y_labels: Option<&'p Formatter>,
+        annotation: Option<&'p Annotation>,
+        (palette, grid, font): (&'p Palette, Grid, &'p Font),
+    ) -> Self {
+        Self {
+            lines,
+            style: SeriesStyle::Line,
+            candles: None,
+            line_options,
+            y_desc,
+            y_labels,
+            annotation,
+            markers: &[],
+            spans: &[],
+            weekends: &[],
+            vlines: &[],
+            overlays: &[],
+            band: None,
+            crossovers: &[],
+            change: None,
+            volume: None,
+            palette,
+            grid,
+            font,
+        }
+    }
+}
+
+/// Bars of `points`, each reaching most of the way to the next one. With
+/// more points than fit `width` pixels at 3 per bar, a bar spans a run of
+/// them and is as high as the highest, so that they don't merge into a block
+/// and spikes still show.
+fn bars(points: &[(DateTime<Utc>, f64)], width: u32) -> Vec<(Range<DateTime<Utc>>, f64)> {
+    let per_bar = points
+        .len()
+        .div_ceil(usize::try_from(width / 3).unwrap_or(usize::MAX).max(1))
+        .max(1);
+    let runs: Vec<_> = points.chunks(per_bar).collect();
+    let starts: Vec<_> = runs.iter().map(|run| run[0].0).collect();
+    runs.iter()
+        .enumerate()
+        .map(|(i, run)| {
+            // the last bar is as wide as the one before
+            let step = match (starts.get(i + 1), i.checked_sub(1)) {
+                (Some(&next), _) => next - starts[i],
+                (None, Some(previous)) => starts[i] - starts[previous],
+                (None, None) => chrono::Duration::zero(),
+            };
+            let value = run.iter().map(|&(_, value)| value).fold(f64::MIN, f64::max);
+            (starts[i]..starts[i] + step * 4 / 5, value)
+        })
+        .collect()
+}
+
+/// Draws `points` in `style`, listed in the legend if they have a `label`.
+fn draw_styled<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    points: Vec<(DateTime<Utc>, f64)>,
+    style: SeriesStyle,
+    color: RGBColor,
+    label: Option<String>,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    match style {
+        SeriesStyle::Line => {
+            let series = chart.draw_series(LineSeries::new(points, color))?;
+            if let Some(label) = label {
+                series
+                    .label(label)
+                    .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+            }
+        }
+        SeriesStyle::Area => {
+            // the bottom of the panel, the window minimum or zero
+            let baseline = chart.y_range().start;
+            let fill = color.mix(0.2);
+            let series =
+                chart.draw_series(AreaSeries::new(points, baseline, fill).border_style(color))?;
+            if let Some(label) = label {
+                series.label(label).legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 20, y + 5)], fill.filled())
+                });
+            }
+        }
+        SeriesStyle::Scatter => {
+            let series = chart.draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
+                points,
+                2,
+                color.filled(),
+            ))?;
+            if let Some(label) = label {
+                series
+                    .label(label)
+                    .legend(move |(x, y)| Circle::new((x + 10, y), 2, color.filled()));
+            }
+        }
+        SeriesStyle::Bar => {
+            let (width, _) = chart.plotting_area().dim_in_pixel();
+            let fill = color.mix(0.6).filled();
+            let series =
+                chart.draw_series(bars(&points, width).into_iter().map(|(span, value)| {
+                    Rectangle::new([(span.start, 0.0), (span.end, value)], fill)
+                }))?;
+            if let Some(label) = label {
+                series
+                    .label(label)
+                    .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], fill));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draws `candles` in the colors of `palette` for rising and falling prices,
+/// listed in the legend if they have a `label`.
+fn draw_candles<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    candles: &[Candle],
+    label: Option<String>,
+    palette: &Palette,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    // leave gaps of about two thirds of a candle between them
+    let (width, _) = chart.plotting_area().dim_in_pixel();
+    let count = u32::try_from(candles.len()).unwrap_or(u32::MAX);
+    let candle_width = (width * 3 / 5 / count).max(1);
+    let (up, down) = (palette.up, palette.down);
+    let series = chart.draw_series(candles.iter().map(|candle| {
+        CandleStick::new(
+            candle.timestamp,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            up.filled(),
+            down.filled(),
+            candle_width,
+        )
+    }))?;
+    if let Some(label) = label {
+        series.label(label).legend(move |(x, y)| {
+            EmptyElement::at((x, y))
+                + Rectangle::new([(0, -5), (9, 5)], up.filled())
+                + Rectangle::new([(11, -5), (20, 5)], down.filled())
+        });
+    }
+    Ok(())
+}
+
+/// Shades the area between the lines of `band`.
+fn draw_band<'a, DB, Y>(chart: &mut DateChart<'a, DB, Y>, band: &Band) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    // along the upper line and back along the lower one
+    let outline = band
+        .points
+        .iter()
+        .map(|&(timestamp, _, upper)| (timestamp, upper))
+        .chain(
+            band.points
+                .iter()
+                .rev()
+                .map(|&(timestamp, lower, _)| (timestamp, lower)),
+        );
+    let style = band.color.mix(0.2).filled();
+    chart
+        .draw_series([Polygon::new(outline.collect::<Vec<_>>(), style)])?
+        .label(&band.label)
+        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], style));
+    Ok(())
+}
+
+/// Shades the intervals of `spans` from the bottom to the top of `chart`.
+fn draw_spans<'a, DB, Y>(chart: &mut DateChart<'a, DB, Y>, spans: &[Span]) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let y_range = chart.y_range();
+    for span in spans {
+        let style = span.color.mix(0.15).filled();
+        chart
+            .draw_series([Rectangle::new(
+                [
+                    (span.range.start, y_range.start),
+                    (span.range.end, y_range.end),
+                ],
+                style,
+            )])?
+            .label(&span.label)
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], style));
+    }
+    Ok(())
+}
+
+/// Draws `crossovers` as triangles pointing the way the fast average crosses,
+/// in the colors of `palette` for rising and falling candles.
+fn draw_crossovers<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    crossovers: &[Crossover],
+    palette: &Palette,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    for (kind, color, corners) in [
+        (Cross::Golden, palette.up, [(0, -6), (-6, 5), (6, 5)]),
+        (Cross::Death, palette.down, [(0, 6), (-6, -5), (6, -5)]),
+    ] {
+        let points: Vec<_> = crossovers
+            .iter()
+            .filter(|crossover| crossover.kind == kind)
+            .map(|crossover| crossover.point)
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        let style = color.filled();
+        chart
+            .draw_series(
+                points
+                    .into_iter()
+                    .map(|point| EmptyElement::at(point) + Polygon::new(corners, style)),
+            )?
+            .label(kind.name())
+            .legend(move |(x, y)| {
+                let corners = corners.map(|(dx, dy)| (x + 10 + dx, y + dy));
+                Polygon::new(corners, style)
+            });
+    }
+    Ok(())
+}
+
+/// Shades `weekends` from the bottom to the top of `chart`, listed once in
+/// the legend.
+fn draw_weekends<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    weekends: &[Range<DateTime<Utc>>],
+    palette: &Palette,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    if weekends.is_empty() {
+        return Ok(());
+    }
+    let y_range = chart.y_range();
+    let style = palette.foreground.mix(0.06).filled();
+    chart
+        .draw_series(weekends.iter().map(|weekend| {
+            Rectangle::new(
+                [(weekend.start, y_range.start), (weekend.end, y_range.end)],
+                style,
+            )
+        }))?
+        .label("weekends")
+        .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], style));
+    Ok(())
+}
+
+/// Draws `markers` with their labels beside them, towards the center of
+/// `chart` so that they aren't cut off at its edges.
+fn draw_markers<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    markers: &[Marker],
+    font: &Font,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let (x_range, y_range) = (chart.x_range(), chart.y_range());
+    let x_middle = x_range.start + (x_range.end - x_range.start) / 2;
+    let y_middle = f64::midpoint(y_range.start, y_range.end);
+    for marker in markers {
+        let (when, value) = marker.point;
+        let (h_pos, dx) = if when < x_middle {
+            (HPos::Left, 8)
+        } else {
+            (HPos::Right, -8)
+        };
+        let (v_pos, dy) = if value < y_middle {
+            (VPos::Bottom, -4)
+        } else {
+            (VPos::Top, 4)
+        };
+        let style = font
+            .sized(12)
+            .color(&marker.color)
+            .pos(Pos::new(h_pos, v_pos));
+        chart.draw_series([EmptyElement::at(marker.point)
+            + Circle::new((0, 0), 5, marker.color.filled())
+            + Text::new(marker.label.clone(), (dx, dy), style)])?;
+    }
+    Ok(())
+}
+
+/// Draws dashed vertical lines from the bottom to the top of `chart`, each
+/// labeled along its upper end.
+fn draw_vlines<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    vlines: &[Annotation],
+    palette: &Palette,
+    font: &Font,
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let (x_range, y_range) = (chart.x_range(), chart.y_range());
+    let color = palette.annotation;
+    let style = TextStyle::from(font.sized(12))
+        .transform(FontTransform::Rotate90)
+        .color(&color);
+    for vline in vlines
+        .iter()
+        .filter(|vline| (x_range.start..=x_range.end).contains(&vline.when))
+    {
+        chart.draw_series(DashedLineSeries::new(
+            [(vline.when, y_range.start), (vline.when, y_range.end)],
+            5,
+            5,
+            color.into(),
+        ))?;
+        // just right of the line, reading downwards from the top
+        chart.draw_series([EmptyElement::at((vline.when, y_range.end))
+            + Text::new(vline.label.clone(), (16, 4), style.clone())])?;
+    }
+    Ok(())
+}
+
+/// How many dates to label on an x axis `width` pixels wide and how to
+/// format them, the day for windows up to a year and the month beyond.
+fn date_labels(x_range: &Range<DateTime<Utc>>, width: u32) -> (usize, &'static str) {
+    // roughly what plotters labels at the default width, but fewer on
+    // narrower charts so the rotated labels don't overlap
+    let count = (width / 90).clamp(2, 10) as usize;
+    let format = if x_range.end - x_range.start > chrono::Duration::days(365) {
+        "%b '%y"
+    } else {
+        "%Y-%m-%d"
+    };
+    (count, format)
+}
+
+/// Draws the mesh of `chart` with dates on the x axis, described by `y_desc`
+/// and labeled with `y_labels` on the y axis.
+fn draw_mesh<'a, DB, Y>(
+    chart: &mut DateChart<'a, DB, Y>,
+    y_desc: &str,
+    y_labels: Option<&Formatter>,
+    (palette, grid, font): (&Palette, Grid, &Font),
+) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let foreground = &palette.foreground;
+    let (width, _) = chart.plotting_area().dim_in_pixel();
+    let (x_labels, x_format) = date_labels(&chart.x_range(), width);
+    let x_label_formatter = |when: &DateTime<Utc>| {
+        when.with_timezone(&grid.timezone)
+            .format(x_format)
+            .to_string()
+    };
+    let mut mesh = chart.configure_mesh();
+    mesh.y_desc(y_desc)
+        .bold_line_style(foreground.mix(0.2))
+        .light_line_style(foreground.mix(0.1))
+        .axis_style(foreground)
+        // the size plotters would pick by default
+        .y_label_style((
+            font.family(),
+            (12.0 * font.scale).percent().max(font.pixels(12)),
+            foreground,
+        ))
+        .x_label_style(
+            TextStyle::from(font.sized(10))
+                .transform(FontTransform::Rotate270)
+                .color(foreground),
+        )
+        .x_labels(x_labels)
+        .x_label_formatter(&x_label_formatter);
+    if let Some(y_labels) = y_labels {
+        mesh.y_label_formatter(y_labels);
+    }
+    grid.configure(&mut mesh);
+    mesh.draw()?;
+    Ok(())
+}
+
+/// Draws the mesh, the lines, the overlays and the annotation of `panel`.
+fn draw_line<'a, DB, Y>(chart: &mut DateChart<'a, DB, Y>, panel: Panel<'_>) -> Result<(), Error>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let Panel {
+        lines,
+        style,
+        candles,
+        line_options,
+        y_desc,
+        y_labels,
+        annotation,
+        markers,
+        spans,
+        weekends,
+        vlines,
+        overlays,
+        band,
+        crossovers,
+        change,
+        // see draw_with_volume
+        volume: _,
+        palette,
+        grid,
+        font,
+    } = panel;
+    draw_mesh(chart, y_desc, y_labels, (palette, grid, font))?;
+
+    draw_weekends(chart, weekends, palette)?;
+    draw_spans(chart, spans)?;
+
+    if let Some(band) = band.filter(|band| !band.points.is_empty()) {
+        draw_band(chart, band)?;
+    }
+
+    let first = lines.first().map(|(_, series)| series.clone());
+    match candles {
+        Some(candles) => {
+            let label = lines.into_iter().next().map(|(label, _)| label);
+            draw_candles(chart, candles, label, palette)?;
+        }
+        None => {
+            for ((label, series), &color) in lines.into_iter().zip(palette.lines.iter().cycle()) {
+                let mut segments = segments(series, line_options).into_iter();
+                let first = segments.next().unwrap_or_default();
+                draw_styled(chart, first, style, color, Some(label))?;
+                for segment in segments {
+                    draw_styled(chart, segment, style, color, None)?;
+                }
+            }
+        }
+    }
+
+    for overlay in overlays.iter().filter(|overlay| !overlay.points.is_empty()) {
+        let color = overlay.color;
+        let points = match line_options.max_points {
+            Some(max_points) => downsample(&overlay.points, max_points),
+            None => overlay.points.clone(),
+        };
+        let series = if overlay.dashed {
+            chart.draw_series(DashedLineSeries::new(points, 5, 5, color.into()))?
+        } else {
+            chart.draw_series(LineSeries::new(points, color))?
+        };
+        series
+            .label(&overlay.label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+
+    draw_vlines(chart, vlines, palette, font)?;
+
+    draw_crossovers(chart, crossovers, palette)?;
+    draw_markers(chart, markers, font)?;
+
+    if let Some(change) = change {
+        let style = font.sized(14).style(FontStyle::Bold).color(&change.color);
+        chart.plotting_area().strip_coord_spec().draw(&Text::new(
+            change.label.as_str(),
+            (8, 8),
+            style,
+        ))?;
+    }
+
+    // an annotation outside of the plotted range would stick to the edge
+    let x_range = chart.x_range();
+    let annotation =
+        annotation.filter(|annotation| (x_range.start..=x_range.end).contains(&annotation.when));
+    if let (Some(annotation), Some(series)) = (annotation, first) {
+        if let Some(value) = nearest_value(series, annotation.when) {
+            let color = palette.annotation;
+            chart
+                .draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
+                    [(annotation.when, value)],
+                    5,
+                    color,
+                ))?
+                .label(&annotation.label)
+                .legend(move |(x, y)| Circle::new((x + 10, y), 5, color));
+        }
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(palette.background.mix(0.8))
+        .border_style(palette.foreground)
+        .label_font(font.sized(12).color(&palette.foreground))
+        .draw()?;
+
+    Ok(())
+}
+
+/// How a chart is drawn, whatever its data. The defaults are those of the
+/// command line.
+#[derive(Clone, Debug)]
+pub struct PlotOptions {
+    /// Of the coin drawn by [`render_svg`], the coins of a [`Chart`] are
+    /// named on their own.
+    pub name: String,
+    /// Of the currency of the prices, e.g. `USD`.
+    pub currency: String,
+    /// Width and height in pixels.
+    pub size: (u32, u32),
+    /// Drawn above all panels.
+    pub title: Option<String>,
+    pub log_scale: bool,
+    /// Whether the prices are indexed to 100, see [`Data::index_prices`].
+    pub normalized: bool,
+    pub theme: Theme,
+    /// Of the theme, with the colors given on the command line instead.
+    pub palette: Palette,
+    /// Drawn in small print in a corner.
+    pub watermark: Option<String>,
+    /// Draw the volume of the first coin behind its prices instead of on a
+    /// panel of its own.
+    pub volume_overlay: bool,
+    /// Which panels to plot, besides indicators in panels of their own.
+    pub metrics: Vec<Metric>,
+    /// Of the price panel and of all others showing amounts of the currency.
+    pub label_styles: (LabelStyle, LabelStyle),
+    /// Of the prices, the other series are drawn as lines.
+    pub series_style: SeriesStyle,
+    /// Of the panel of the volume.
+    pub volume_style: SeriesStyle,
+    pub panel_styles: PanelStyles,
+    pub line_options: LineOptions,
+    pub grid: Grid,
+    pub font: Font,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            name: "Ethereum".to_owned(),
+            currency: "USD".to_owned(),
+            size: (1024, 768),
+            title: None,
+            log_scale: false,
+            normalized: false,
+            theme: Theme::Light,
+            palette: *Theme::Light.palette(),
+            watermark: None,
+            volume_overlay: false,
+            metrics: Metric::ALL.to_vec(),
+            label_styles: (LabelStyle::Plain, LabelStyle::Plain),
+            series_style: SeriesStyle::Line,
+            volume_style: SeriesStyle::Line,
+            panel_styles: PanelStyles::default(),
+            line_options: LineOptions {
+                broken: false,
+                max_points: None,
+            },
+            grid: Grid {
+                x_labels: None,
+                y_labels: None,
+                lines: true,
+                timezone: Tz::UTC,
+            },
+            font: Font {
+                family: "sans-serif".to_owned(),
+                scale: 1.0,
+            },
+        }
+    }
+}
+
+/// Plots the prices, market caps and volumes of `data` as SVG with
+/// `options`, without touching the filesystem.
+///
+/// # Errors
+///
+/// If there are no prices to plot, none of the `metrics` has data or
+/// plotters fails to draw them.
+pub fn render_svg(data: &Data, options: &PlotOptions) -> Result<String, Error> {
+    let coins = vec![Coin {
+        name: options.name.clone(),
+        data: data.clone(),
+    }];
+    let chart = Chart {
+        ranges: Ranges::new(&coins, None, &options.metrics)?,
+        coins,
+        days: None,
+        decorations: Decorations::default(),
+        candles: None,
+        options: options.clone(),
+    };
+    chart.svg()
+}
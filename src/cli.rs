@@ -0,0 +1,80 @@
+//! How the options of the library are named on the command line.
+
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+use crate::chart::{Metric, SeriesStyle};
+use crate::labels::LabelStyle;
+use crate::theme::Theme;
+use crate::PriceField;
+
+impl ValueEnum for SeriesStyle {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Line, Self::Area, Self::Scatter]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Line => PossibleValue::new("line"),
+            Self::Area => PossibleValue::new("area"),
+            Self::Scatter => PossibleValue::new("scatter"),
+            Self::Bar => return None,
+        })
+    }
+}
+
+impl ValueEnum for Metric {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Price => PossibleValue::new("price"),
+            Self::MarketCap => PossibleValue::new("market_cap").alias("market-cap"),
+            Self::Volume => PossibleValue::new("volume"),
+        })
+    }
+}
+
+impl ValueEnum for LabelStyle {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Plain, Self::Grouped, Self::Compact]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Plain => PossibleValue::new("plain"),
+            Self::Grouped => PossibleValue::new("grouped"),
+            Self::Compact => PossibleValue::new("compact"),
+        })
+    }
+}
+
+impl ValueEnum for Theme {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Light, Self::Dark]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Light => PossibleValue::new("light"),
+            Self::Dark => PossibleValue::new("dark"),
+        })
+    }
+}
+
+impl ValueEnum for PriceField {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Open, Self::High, Self::Low, Self::Close]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Open => PossibleValue::new("open"),
+            Self::High => PossibleValue::new("high"),
+            Self::Low => PossibleValue::new("low"),
+            Self::Close => PossibleValue::new("close"),
+        })
+    }
+}
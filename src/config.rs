@@ -0,0 +1,49 @@
+//! Defaults read from a TOML file, overridden by the command line.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use eth_hist_plot::days::Days;
+use eth_hist_plot::Error;
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub coin: Option<String>,
+    pub currency: Option<String>,
+    pub days: Option<Days>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub output: Option<PathBuf>,
+}
+
+impl Config {
+    /// `config.toml` in the platform's configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(
+            dirs::config_dir()?
+                .join(env!("CARGO_PKG_NAME"))
+                .join("config.toml"),
+        )
+    }
+
+    /// Reads the configuration at `path`, a missing file yields the empty
+    /// configuration.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(Error::Io(io::Error::new(
+                    err.kind(),
+                    format!("cannot read {}: {err}", path.display()),
+                )))
+            }
+        };
+
+        toml::from_str(&content)
+            .map_err(|err| Error::InvalidArgs(format!("{}: {err}", path.display())))
+    }
+}
@@ -0,0 +1,159 @@
+//! How much history to fetch.
+
+use std::fmt;
+
+use chrono::{DateTime, Months, Utc};
+use serde::Deserialize;
+
+/// Days of history to fetch. The API picks the granularity of the data
+/// from it: 5-minutely for 1 day, hourly for up to 90 days and daily
+/// beyond that and for `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Raw")]
+pub enum Days {
+    Count(u32),
+    Max,
+}
+
+impl Days {
+    /// Values documented for the API, others may yield a granularity that
+    /// comes as a surprise.
+    const DOCUMENTED: [Self; 8] = [
+        Self::Count(1),
+        Self::Count(7),
+        Self::Count(14),
+        Self::Count(30),
+        Self::Count(90),
+        Self::Count(180),
+        Self::Count(365),
+        Self::Max,
+    ];
+
+    #[must_use]
+    pub fn is_documented(self) -> bool {
+        Self::DOCUMENTED.contains(&self)
+    }
+
+    /// Describes the granularity of the data the API returns.
+    #[must_use]
+    pub fn granularity(self) -> &'static str {
+        match self {
+            Self::Count(1) => "5-minutely",
+            Self::Count(2..=90) => "hourly",
+            Self::Count(_) | Self::Max => "daily",
+        }
+    }
+}
+
+impl fmt::Display for Days {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count(days) => write!(f, "{days}"),
+            Self::Max => f.write_str("max"),
+        }
+    }
+}
+
+/// Parses a positive number of days, a time ago such as `6m`, see
+/// [`Ago::parse`], or `max`.
+///
+/// # Errors
+///
+/// Describes for the command line what `s` is instead.
+pub fn parse(s: &str) -> Result<Days, String> {
+    if s == "max" {
+        return Ok(Days::Max);
+    }
+
+    let days = match s.parse() {
+        Ok(days) => days,
+        Err(_) if s.starts_with(|c: char| c.is_ascii_digit()) => {
+            let now = Utc::now();
+            let days = (now - Ago::parse(s)?.before(now)).num_days();
+            u32::try_from(days).map_err(|_| format!("`{s}` is too long ago"))?
+        }
+        Err(_) => return Err(format!("`{s}` is neither a number of days nor `max`")),
+    };
+    if days == 0 {
+        return Err("days must be greater than zero".to_owned());
+    }
+    Ok(Days::Count(days))
+}
+
+/// A time ago in calendar months and days, e.g. `6m` or `1y2w`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ago {
+    months: u32,
+    days: u32,
+}
+
+impl Ago {
+    /// Parses amounts of days (`d`), weeks (`w`), months (`m`) and years
+    /// (`y`), which add up, e.g. `90d` or `1y6m`. Fails with the first
+    /// amount that is none of them.
+    ///
+    /// # Errors
+    ///
+    /// Describes for the command line what is not an amount.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = |token: &str| {
+            format!(
+                "`{token}` is not an amount of days, weeks, months or years like `90d`, `2w`, \
+                 `6m` or `1y`"
+            )
+        };
+        if s.is_empty() {
+            return Err(invalid(s));
+        }
+
+        let mut ago = Self::default();
+        let mut rest = s;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let unit = rest[digits..].chars().next().map_or(0, char::len_utf8);
+            let (token, tail) = rest.split_at(digits + unit);
+            let amount: u32 = token[..digits].parse().map_err(|_| invalid(token))?;
+            let (months, days) = match &token[digits..] {
+                "d" => (0, amount),
+                "w" => (0, amount.checked_mul(7).ok_or_else(|| invalid(token))?),
+                "m" => (amount, 0),
+                "y" => (amount.checked_mul(12).ok_or_else(|| invalid(token))?, 0),
+                _ => return Err(invalid(token)),
+            };
+            ago.months = ago.months.checked_add(months).ok_or_else(|| invalid(s))?;
+            ago.days = ago.days.checked_add(days).ok_or_else(|| invalid(s))?;
+            rest = tail;
+        }
+        Ok(ago)
+    }
+
+    /// The time this long before `now`, months first, at the end of the month
+    /// if it is shorter. The earliest time there is if it is out of range.
+    #[must_use]
+    pub fn before(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now.checked_sub_months(Months::new(self.months))
+            .and_then(|then| then.checked_sub_signed(chrono::Duration::days(self.days.into())))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+/// What a configuration may contain, `days = 30` or `days = "max"`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Raw {
+    Count(u32),
+    Text(String),
+}
+
+impl TryFrom<Raw> for Days {
+    type Error = String;
+
+    fn try_from(raw: Raw) -> Result<Self, Self::Error> {
+        match raw {
+            Raw::Count(days) => parse(&days.to_string()),
+            Raw::Text(s) => parse(&s),
+        }
+    }
+}
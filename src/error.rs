@@ -0,0 +1,152 @@
+//! What can go wrong between fetching the data and drawing the chart.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use plotters::drawing::DrawingAreaErrorKind;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Requesting the data from the API failed.
+    Http(Box<ureq::Error>),
+    /// The API responded with an error status, e.g. 429 if rate limited.
+    /// The message includes what the API tells about the error.
+    Status(u16, String),
+    /// The API didn't respond within the given time.
+    Timeout(Duration),
+    /// The proxy requests are sent through can't be reached.
+    Proxy(String),
+    /// The API responded, but not with the data asked for, e.g. because the
+    /// coin is unknown.
+    Api(String),
+    /// Fetching is forbidden and the response isn't cached.
+    NotCached(String),
+    /// Reading or writing JSON failed.
+    Parse(serde_json::Error),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// There is nothing to plot.
+    EmptyData(String),
+    /// The series of the data don't describe the same points in time.
+    Inconsistent(String),
+    /// The command line or the configuration asks for something impossible.
+    InvalidArgs(String),
+    /// Drawing the chart failed.
+    Plot(Box<dyn error::Error + Send + Sync>),
+    /// Fetching some of several coins failed, each paired with its id.
+    Fetch(Vec<(String, Error)>),
+}
+
+impl Error {
+    /// Code for the process to exit with, by what went wrong:
+    ///
+    /// - 1: anything else, e.g. reading or writing a file or drawing failed
+    /// - 2: the arguments or the configuration are invalid, as for usage
+    ///   errors of the command line
+    /// - 3: getting the data from the API failed
+    /// - 4: there is no data to plot
+    /// - 5: the data is malformed
+    ///
+    /// Several failed fetches exit with the code of the first.
+    #[must_use]
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Io(_) | Self::Plot(_) => 1,
+            Self::InvalidArgs(_) => 2,
+            Self::Http(_)
+            | Self::Status(..)
+            | Self::Timeout(_)
+            | Self::Proxy(_)
+            | Self::Api(_)
+            | Self::NotCached(_) => 3,
+            Self::EmptyData(_) => 4,
+            Self::Parse(_) | Self::Inconsistent(_) => 5,
+            Self::Fetch(failures) => failures.first().map_or(3, |(_, err)| err.exit_code()),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => err.fmt(f),
+            Self::Timeout(timeout) => {
+                write!(f, "request timed out after {}s", timeout.as_secs())
+            }
+            Self::NotCached(key) => {
+                write!(f, "{key} is not cached, and --offline forbids fetching it")
+            }
+            Self::Status(_, msg)
+            | Self::Proxy(msg)
+            | Self::Api(msg)
+            | Self::EmptyData(msg)
+            | Self::Inconsistent(msg)
+            | Self::InvalidArgs(msg) => f.write_str(msg),
+            Self::Parse(err) => err.fmt(f),
+            Self::Io(err) => err.fmt(f),
+            Self::Plot(err) => err.fmt(f),
+            Self::Fetch(failures) => {
+                if let [(coin, err)] = failures.as_slice() {
+                    return write!(f, "{coin}: {err}");
+                }
+                write!(f, "cannot fetch {} coins", failures.len())?;
+                for (i, (coin, err)) in failures.iter().enumerate() {
+                    let separator = if i == 0 { ":" } else { ";" };
+                    write!(f, "{separator} {coin}: {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Http(err) => Some(err),
+            Self::Parse(err) => Some(err),
+            Self::Io(err) => Some(err),
+            Self::Plot(err) => Some(&**err),
+            Self::Status(..)
+            | Self::Timeout(_)
+            | Self::Proxy(_)
+            | Self::Api(_)
+            | Self::NotCached(_)
+            | Self::EmptyData(_)
+            | Self::Inconsistent(_)
+            | Self::InvalidArgs(_)
+            | Self::Fetch(_) => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Self {
+        Self::Http(Box::new(err))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<E> From<DrawingAreaErrorKind<E>> for Error
+where
+    E: error::Error + Send + Sync + 'static,
+{
+    fn from(err: DrawingAreaErrorKind<E>) -> Self {
+        Self::Plot(Box::new(err))
+    }
+}
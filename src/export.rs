@@ -0,0 +1,50 @@
+//! Writing the data to files for use by other tools.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use chrono::SecondsFormat;
+use eth_hist_plot::{Data, Error};
+
+/// Writes one row per timestamp, see [`Data::join`], with the columns
+/// `timestamp,price,market_cap,total_volume`. Timestamps are RFC 3339,
+/// missing values are left empty.
+pub fn write_csv(path: &Path, data: &Data) -> Result<(), Error> {
+    let mut writer = create(path)?;
+
+    let cell = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+    writeln!(writer, "timestamp,price,market_cap,total_volume")?;
+    for row in data.join() {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            row.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            cell(row.price),
+            cell(row.market_cap),
+            cell(row.total_volume)
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes an array of objects with the keys `timestamp`, `price`,
+/// `market_cap` and `volume`, one per timestamp, see [`Data::join`].
+pub fn write_json(path: &Path, data: &Data) -> Result<(), Error> {
+    let mut writer = create(path)?;
+    serde_json::to_writer_pretty(&mut writer, &data.join())?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn create(path: &Path) -> io::Result<BufWriter<File>> {
+    let file = File::create(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("cannot create {}: {err}", path.display()),
+        )
+    })?;
+    Ok(BufWriter::new(file))
+}
@@ -0,0 +1,60 @@
+//! Telling whether a chart would come out as the one written last time.
+
+use std::ffi::OsString;
+use std::fs;
+use std::hash::Hasher;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use eth_hist_plot::Error;
+use fnv::FnvHasher;
+
+/// A hash of everything a chart is drawn from. FNV rather than the hasher
+/// of the standard library, which may change between Rust releases.
+pub struct Fingerprint(FnvHasher);
+
+impl Fingerprint {
+    /// Starts with the version, a new release may draw differently, and the
+    /// command line.
+    pub fn new(args: impl IntoIterator<Item = OsString>) -> Self {
+        let mut fingerprint = Self(FnvHasher::default());
+        fingerprint.add(env!("CARGO_PKG_VERSION").as_bytes());
+        for arg in args {
+            fingerprint.add(arg.as_encoded_bytes());
+        }
+        fingerprint
+    }
+
+    /// Adds `bytes`, ended so that the boundaries between them count.
+    pub fn add(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+        self.0.write_usize(bytes.len());
+    }
+
+    pub fn hex(&self) -> String {
+        format!("{:016x}", self.0.finish())
+    }
+}
+
+/// The file next to `output` keeping the fingerprint of its chart.
+fn sidecar(output: &Path) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+    path.push(".hash");
+    PathBuf::from(path)
+}
+
+/// Whether `output` exists and was drawn from what has the fingerprint `hex`.
+pub fn unchanged(output: &Path, hex: &str) -> bool {
+    output.exists() && fs::read_to_string(sidecar(output)).is_ok_and(|saved| saved.trim() == hex)
+}
+
+/// Keeps `hex` as the fingerprint of the chart just written to `output`.
+pub fn save(output: &Path, hex: &str) -> Result<(), Error> {
+    let path = sidecar(output);
+    fs::write(&path, format!("{hex}\n")).map_err(|err| {
+        Error::Io(io::Error::new(
+            err.kind(),
+            format!("cannot write {}: {err}", path.display()),
+        ))
+    })
+}
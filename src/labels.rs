@@ -0,0 +1,126 @@
+//! Formatting of the values on the y axes.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelStyle {
+    /// As plotters formats them.
+    Plain,
+    /// With thousands separators and the currency, e.g. `$412,847,193,847`.
+    Grouped,
+    /// With a suffix for thousands, millions and so on, e.g. `$412.8B`.
+    Compact,
+}
+
+/// Turns a value on an axis into its label.
+pub type Formatter = dyn Fn(&f64) -> String;
+
+/// Formats values in `style`, with `currency` if the values are amounts of
+/// it. Returns `None` for [`LabelStyle::Plain`], which is left to plotters.
+pub fn formatter(style: LabelStyle, currency: Option<&str>) -> Option<Box<Formatter>> {
+    let unit = currency.map(Unit::of);
+    match style {
+        LabelStyle::Plain => None,
+        LabelStyle::Grouped => Some(Box::new(move |&value| {
+            with_unit(&grouped(value), unit.as_ref(), value)
+        })),
+        LabelStyle::Compact => Some(Box::new(move |&value| {
+            with_unit(&compact(value), unit.as_ref(), value)
+        })),
+    }
+}
+
+/// Formats values indexed to 100 as the change from it in percent, e.g.
+/// `+50%` for 150. Decimals are only written where they matter.
+#[must_use]
+pub fn percent_change() -> Box<Formatter> {
+    Box::new(|&value| {
+        let change = value - 100.0;
+        if change.abs() < 1e-9 {
+            "0%".to_owned()
+        } else if (change - change.round()).abs() < 1e-9 {
+            format!("{change:+.0}%")
+        } else {
+            format!("{change:+.1}%")
+        }
+    })
+}
+
+/// How an amount of a currency is written.
+enum Unit {
+    /// Put in front of the number, e.g. `$`.
+    Symbol(&'static str),
+    /// Put after the number, e.g. `CHF`.
+    Code(String),
+}
+
+impl Unit {
+    fn of(currency: &str) -> Self {
+        match currency.to_ascii_lowercase().as_str() {
+            "usd" => Self::Symbol("$"),
+            "eur" => Self::Symbol("€"),
+            "gbp" => Self::Symbol("£"),
+            "jpy" | "cny" => Self::Symbol("¥"),
+            "inr" => Self::Symbol("₹"),
+            "krw" => Self::Symbol("₩"),
+            "btc" => Self::Symbol("₿"),
+            "eth" => Self::Symbol("Ξ"),
+            _ => Self::Code(currency.to_ascii_uppercase()),
+        }
+    }
+}
+
+/// Adds `unit` to `number`, the formatted absolute `value`.
+fn with_unit(number: &str, unit: Option<&Unit>, value: f64) -> String {
+    let sign = if value < 0.0 { "-" } else { "" };
+    match unit {
+        Some(Unit::Symbol(symbol)) => format!("{sign}{symbol}{number}"),
+        Some(Unit::Code(code)) => format!("{sign}{number} {code}"),
+        None => format!("{sign}{number}"),
+    }
+}
+
+/// Formats the absolute `value` with thousands separators, and with
+/// decimals only where they matter.
+fn grouped(value: f64) -> String {
+    let value = value.abs();
+    let decimals = if value >= 1000.0 {
+        0
+    } else if (f64::MIN_POSITIVE..1.0).contains(&value) {
+        4
+    } else {
+        2
+    };
+    let formatted = format!("{value:.decimals$}");
+    let (integer, fraction) = formatted
+        .split_once('.')
+        .map_or((formatted.as_str(), None), |(integer, fraction)| {
+            (integer, Some(fraction))
+        });
+
+    let mut result = String::with_capacity(formatted.len() + integer.len() / 3);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        result.push('.');
+        result.push_str(fraction);
+    }
+    result
+}
+
+/// Formats the absolute `value` with one decimal and a suffix for its
+/// magnitude, values below a thousand are [`grouped`].
+fn compact(value: f64) -> String {
+    const SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+    let value = value.abs();
+    SUFFIXES
+        .iter()
+        .find(|&&(magnitude, _)| value >= magnitude)
+        .map_or_else(
+            || grouped(value),
+            |&(magnitude, suffix)| format!("{:.1}{suffix}", value / magnitude),
+        )
+}
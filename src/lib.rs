@@ -0,0 +1,1156 @@
+// https://www.coingecko.com/en/api/documentation
+
+#![warn(rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Range;
+use std::slice::Iter;
+
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+mod axis;
+pub mod chart;
+#[cfg(feature = "cli")]
+mod cli;
+pub mod days;
+mod error;
+pub mod labels;
+pub mod theme;
+
+pub use chart::{render_svg, PlotOptions};
+pub use error::{Error, Result};
+
+/// Response of the `coins/{id}/market_chart` endpoint, which is also how it
+/// serializes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Data {
+    prices: Vec<Datum>,
+    market_caps: Vec<Datum>,
+    total_volumes: Vec<Datum>,
+}
+
+impl Data {
+    /// Data from other sources than the API, normalized like parsed data.
+    #[must_use]
+    pub fn new(prices: Vec<Datum>, market_caps: Vec<Datum>, total_volumes: Vec<Datum>) -> Self {
+        let mut data = Self {
+            prices,
+            market_caps,
+            total_volumes,
+        };
+        data.normalize();
+        data
+    }
+
+    pub fn iter_prices(&self) -> Iter<'_, Datum> {
+        self.prices.iter()
+    }
+
+    pub fn iter_market_caps(&self) -> Iter<'_, Datum> {
+        self.market_caps.iter()
+    }
+
+    pub fn iter_total_volumes(&self) -> Iter<'_, Datum> {
+        self.total_volumes.iter()
+    }
+
+    /// Sorts all series by time and keeps only the last of several values at
+    /// the same timestamp, so that lines don't zig-zag.
+    pub fn normalize(&mut self) {
+        for series in [
+            &mut self.prices,
+            &mut self.market_caps,
+            &mut self.total_volumes,
+        ] {
+            // stable, so that the last of equal timestamps stays last
+            series.sort_by_key(|datum| datum.0);
+            series.dedup_by(|later, earlier| {
+                let duplicate = later.0 == earlier.0;
+                if duplicate {
+                    earlier.1 = later.1;
+                }
+                duplicate
+            });
+        }
+    }
+
+    /// Checks that the market caps and the total volumes are at the times of
+    /// the prices, give or take `tolerance`. Series left empty, e.g. the
+    /// market caps of sources without them, aren't checked.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first value that's at a different time, or with the
+    /// lengths of the series if they differ.
+    pub fn validate(&self, tolerance: chrono::Duration) -> Result<()> {
+        for (series, what) in [
+            (&self.market_caps, "market cap"),
+            (&self.total_volumes, "total volume"),
+        ] {
+            if series.is_empty() {
+                continue;
+            }
+            if let Some((i, (price, datum))) = self
+                .prices
+                .iter()
+                .zip(series)
+                .enumerate()
+                .find(|(_, (price, datum))| (price.0 - datum.0).abs() > tolerance)
+            {
+                return Err(Error::Inconsistent(format!(
+                    "{what} #{} is at {}, but price #{} at {}",
+                    i + 1,
+                    datum.0,
+                    i + 1,
+                    price.0
+                )));
+            }
+            if series.len() != self.prices.len() {
+                return Err(Error::Inconsistent(format!(
+                    "there are {} {what}s, but {} prices",
+                    series.len(),
+                    self.prices.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Turns NaN and infinite values of all series into missing ones, which
+    /// would otherwise end up as the extremes of the plotted ranges. Returns
+    /// how many there were.
+    pub fn drop_non_finite(&mut self) -> usize {
+        let mut dropped = 0;
+        for series in [
+            &mut self.prices,
+            &mut self.market_caps,
+            &mut self.total_volumes,
+        ] {
+            for datum in series {
+                if datum.1.is_some_and(|value| !value.is_finite()) {
+                    datum.1 = None;
+                    dropped += 1;
+                }
+            }
+        }
+        dropped
+    }
+
+    /// The price at `when`, linearly interpolated between the prices around
+    /// it, skipping missing ones. `None` if `when` is before the first or
+    /// after the last price.
+    #[must_use]
+    pub fn price_at(&self, when: DateTime<Utc>) -> Option<f64> {
+        let mut before: Option<(DateTime<Utc>, f64)> = None;
+        for (timestamp, price) in self
+            .prices
+            .iter()
+            .filter_map(|datum| Some((datum.0, datum.1?)))
+        {
+            if timestamp == when {
+                return Some(price);
+            }
+            if timestamp > when {
+                let (start, start_price) = before?;
+                #[allow(clippy::cast_precision_loss)]
+                let fraction = (when - start).num_milliseconds() as f64
+                    / (timestamp - start).num_milliseconds() as f64;
+                return Some(start_price + (price - start_price) * fraction);
+            }
+            before = Some((timestamp, price));
+        }
+        None
+    }
+
+    /// Rebases the prices so that the first available one becomes 100 and all
+    /// others are relative to it. Does nothing if there's no price at all.
+    pub fn index_prices(&mut self) {
+        let Some(base) = self.prices.iter().find_map(Datum::price) else {
+            return;
+        };
+
+        for datum in &mut self.prices {
+            datum.1 = datum.1.map(|price| price / base * 100.0);
+        }
+    }
+
+    /// Linearly interpolates the missing values of all series over runs of
+    /// at most `max_gap` of them, weighted by time. Longer runs and runs at
+    /// either end of a series stay missing.
+    pub fn fill_gaps(&mut self, max_gap: usize) {
+        for series in [
+            &mut self.prices,
+            &mut self.market_caps,
+            &mut self.total_volumes,
+        ] {
+            fill_gaps(series, max_gap);
+        }
+    }
+
+    /// Finds the intervals between consecutive prices that are more than
+    /// `factor` times longer than the median interval, as pairs of the
+    /// timestamps around them.
+    #[must_use]
+    pub fn price_gaps(&self, factor: f64) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut intervals: Vec<_> = self
+            .prices
+            .windows(2)
+            .map(|pair| pair[1].0 - pair[0].0)
+            .collect();
+        if intervals.is_empty() {
+            return Vec::new();
+        }
+        intervals.sort_unstable();
+        #[allow(clippy::cast_precision_loss)]
+        let threshold = intervals[intervals.len() / 2].num_milliseconds() as f64 * factor;
+
+        self.prices
+            .windows(2)
+            .filter(|pair| {
+                #[allow(clippy::cast_precision_loss)]
+                let interval = (pair[1].0 - pair[0].0).num_milliseconds() as f64;
+                interval > threshold
+            })
+            .map(|pair| (pair[0].0, pair[1].0))
+            .collect()
+    }
+
+    /// Joins the three series on their timestamps. This is an outer join, so
+    /// a timestamp missing from a series leaves its value empty in that row,
+    /// just like a missing value does. Rows are ordered by timestamp.
+    #[must_use]
+    pub fn join(&self) -> Vec<Row> {
+        let mut rows = BTreeMap::new();
+        for datum in &self.prices {
+            Row::entry(&mut rows, datum.0).price = datum.1;
+        }
+        for datum in &self.market_caps {
+            Row::entry(&mut rows, datum.0).market_cap = datum.1;
+        }
+        for datum in &self.total_volumes {
+            Row::entry(&mut rows, datum.0).total_volume = datum.1;
+        }
+
+        rows.into_values().collect()
+    }
+
+    /// Replaces the values of all series by one per calendar `period` they
+    /// fall into, timestamped with the start of the period and aggregated as
+    /// told. Periods whose values are all missing get a missing one, periods
+    /// without any value at all get none.
+    pub fn resample(&mut self, period: Period, aggregate: Aggregate) {
+        for series in [
+            &mut self.prices,
+            &mut self.market_caps,
+            &mut self.total_volumes,
+        ] {
+            resample(series, period, aggregate);
+        }
+    }
+
+    /// Drops everything outside of the inclusive interval from `from` to
+    /// `to`, where a missing bound leaves that side open.
+    pub fn retain_between(&mut self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) {
+        let within = |datum: &Datum| {
+            from.is_none_or(|from| datum.0 >= from) && to.is_none_or(|to| datum.0 <= to)
+        };
+
+        self.prices.retain(within);
+        self.market_caps.retain(within);
+        self.total_volumes.retain(within);
+    }
+}
+
+fn fill_gaps(series: &mut [Datum], max_gap: usize) {
+    let mut previous: Option<(usize, DateTime<Utc>, f64)> = None;
+    for i in 0..series.len() {
+        let Datum(end, Some(end_value)) = series[i] else {
+            continue;
+        };
+
+        if let Some((start_index, start, start_value)) = previous {
+            let gap = &mut series[start_index + 1..i];
+            if !gap.is_empty() && gap.len() <= max_gap {
+                // milliseconds are exact in an f64 for millions of years
+                #[allow(clippy::cast_precision_loss)]
+                let span = (end - start).num_milliseconds() as f64;
+                for datum in gap {
+                    #[allow(clippy::cast_precision_loss)]
+                    let fraction = (datum.0 - start).num_milliseconds() as f64 / span;
+                    datum.1 = Some(start_value + (end_value - start_value) * fraction);
+                }
+            }
+        }
+
+        previous = Some((i, end, end_value));
+    }
+}
+
+fn resample(series: &mut Vec<Datum>, period: Period, aggregate: Aggregate) {
+    let mut resampled: Vec<Datum> = Vec::new();
+    // of the values in the last period
+    let (mut sum, mut count) = (0.0, 0_u32);
+    for datum in series.iter() {
+        let start = period.start(datum.0);
+        if resampled.last().is_none_or(|last| last.0 != start) {
+            resampled.push(Datum(start, None));
+            (sum, count) = (0.0, 0);
+        }
+        let Some(value) = datum.1 else {
+            continue;
+        };
+
+        sum += value;
+        count += 1;
+        resampled.last_mut().expect("just pushed").1 = Some(match aggregate {
+            Aggregate::Mean => sum / f64::from(count),
+            Aggregate::Last => value,
+        });
+    }
+    *series = resampled;
+}
+
+/// Calendar periods to resample to, in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    /// Starting on Mondays.
+    Weekly,
+    Monthly,
+}
+
+impl Period {
+    /// Start of the period containing `when`.
+    #[must_use]
+    pub fn start(self, when: DateTime<Utc>) -> DateTime<Utc> {
+        let date = when.date_naive();
+        let days = match self {
+            Self::Daily => 0,
+            Self::Weekly => date.weekday().num_days_from_monday(),
+            Self::Monthly => date.day0(),
+        };
+        (date - chrono::Days::new(days.into()))
+            .and_time(NaiveTime::MIN)
+            .and_utc()
+    }
+}
+
+/// How the values within a period are summarized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Mean,
+    /// The latest value.
+    Last,
+}
+
+/// A value at a point in time, missing values are `null` in the response.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Datum(
+    #[serde(with = "ts_milliseconds")] DateTime<Utc>,
+    Option<f64>,
+);
+
+impl Datum {
+    #[must_use]
+    pub fn new(timestamp: DateTime<Utc>, value: Option<f64>) -> Self {
+        Self(timestamp, value)
+    }
+
+    #[must_use]
+    pub fn timestamp(&self) -> &DateTime<Utc> {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn price(&self) -> Option<f64> {
+        self.1
+    }
+}
+
+/// All values at one point in time, see [`Data::join`].
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Row {
+    pub timestamp: DateTime<Utc>,
+    pub price: Option<f64>,
+    pub market_cap: Option<f64>,
+    #[serde(rename = "volume")]
+    pub total_volume: Option<f64>,
+}
+
+impl Row {
+    fn entry(rows: &mut BTreeMap<DateTime<Utc>, Self>, timestamp: DateTime<Utc>) -> &mut Self {
+        rows.entry(timestamp).or_insert_with(|| Self {
+            timestamp,
+            price: None,
+            market_cap: None,
+            total_volume: None,
+        })
+    }
+}
+
+/// Open, high, low and close price over an interval, as in the response of
+/// the `coins/{id}/ohlc` endpoint, timestamped with the end of the interval.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RawCandle", into = "RawCandle")]
+pub struct Candle {
+    pub timestamp: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl Candle {
+    /// The price of the candle that is `field`.
+    #[must_use]
+    pub fn price(&self, field: PriceField) -> f64 {
+        match field {
+            PriceField::Open => self.open,
+            PriceField::High => self.high,
+            PriceField::Low => self.low,
+            PriceField::Close => self.close,
+        }
+    }
+}
+
+/// One of the prices of a [`Candle`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceField {
+    Open,
+    High,
+    Low,
+    Close,
+}
+
+/// A [`Candle`] as the array the API returns.
+#[derive(Serialize, Deserialize)]
+struct RawCandle(
+    #[serde(with = "ts_milliseconds")] DateTime<Utc>,
+    f64,
+    f64,
+    f64,
+    f64,
+);
+
+impl From<RawCandle> for Candle {
+    fn from(RawCandle(timestamp, open, high, low, close): RawCandle) -> Self {
+        Self {
+            timestamp,
+            open,
+            high,
+            low,
+            close,
+        }
+    }
+}
+
+impl From<Candle> for RawCandle {
+    fn from(candle: Candle) -> Self {
+        Self(
+            candle.timestamp,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+        )
+    }
+}
+
+/// Summary of a series, see [`Stats::of`].
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    /// Lowest value and when it occurred, the first time if several times.
+    pub min: (DateTime<Utc>, f64),
+    /// Highest value and when it occurred, the first time if several times.
+    pub max: (DateTime<Utc>, f64),
+    pub mean: f64,
+    pub start: f64,
+    pub end: f64,
+    /// Change from `start` to `end` in percent.
+    pub change: f64,
+}
+
+impl Stats {
+    /// Summarizes the values of `series`, ignoring missing ones. Returns
+    /// `None` if there's no value at all.
+    pub fn of<'a>(series: impl IntoIterator<Item = &'a Datum>) -> Option<Self> {
+        let mut values = series
+            .into_iter()
+            .filter_map(|datum| datum.1.map(|value| (datum.0, value)));
+        let first = values.next()?;
+
+        let mut stats = Self {
+            min: first,
+            max: first,
+            mean: 0.0,
+            start: first.1,
+            end: first.1,
+            change: 0.0,
+        };
+        let mut sum = first.1;
+        let mut count = 1_u32;
+        for (timestamp, value) in values {
+            if value < stats.min.1 {
+                stats.min = (timestamp, value);
+            }
+            if value > stats.max.1 {
+                stats.max = (timestamp, value);
+            }
+            sum += value;
+            count += 1;
+            stats.end = value;
+        }
+        stats.mean = sum / f64::from(count);
+        stats.change = (stats.end - stats.start) / stats.start * 100.0;
+
+        Some(stats)
+    }
+}
+
+/// Pairs of lower and upper values over time, see [`bollinger_bands`].
+pub type BandPoints = Vec<(DateTime<Utc>, f64, f64)>;
+
+/// How many consecutive prices of `series` span `days` days at the median
+/// interval between them, so that windows in days fit hourly or 5-minutely
+/// prices as well as daily ones. At least one, and `days` if there are fewer
+/// than two prices to tell the interval from.
+#[must_use]
+pub fn window_len(series: Iter<'_, Datum>, days: usize) -> usize {
+    let timestamps: Vec<_> = series
+        .filter(|x| x.price().is_some())
+        .map(|x| *x.timestamp())
+        .collect();
+    let mut intervals: Vec<_> = timestamps
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds())
+        .collect();
+    if intervals.is_empty() {
+        return days;
+    }
+    intervals.sort_unstable();
+    let interval = intervals[intervals.len() / 2].max(1);
+    let span = i64::try_from(days).map_or(i64::MAX, |days| days.saturating_mul(86_400));
+    // to the nearest whole number of prices
+    let len = span.saturating_add(interval / 2) / interval;
+    usize::try_from(len).map_or(usize::MAX, |len| len.max(1))
+}
+
+/// Simple moving average over the prices of the last `days` days, see
+/// [`window_len`], skipping missing ones. The average starts only once the
+/// window is full, so fewer prices yield no average at all.
+#[must_use]
+pub fn simple_moving_average(series: Iter<'_, Datum>, days: usize) -> Vec<(DateTime<Utc>, f64)> {
+    let window = window_len(series.clone(), days);
+    let prices: Vec<_> = series
+        .filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+        .collect();
+
+    let mut sum = 0.0;
+    let mut average = Vec::with_capacity(prices.len().saturating_sub(window - 1));
+    for (i, &(timestamp, price)) in prices.iter().enumerate() {
+        sum += price;
+        if i >= window {
+            sum -= prices[i - window].1;
+        }
+        if i + 1 >= window {
+            #[allow(clippy::cast_precision_loss)]
+            average.push((timestamp, sum / window as f64));
+        }
+    }
+
+    average
+}
+
+/// Exponential moving average over the prices of the last `days` days, see
+/// [`window_len`], with the smoothing factor `2 / (window + 1)`, skipping
+/// missing prices. Seeded with the first price, so it starts right away but
+/// follows the early prices closely.
+#[must_use]
+pub fn exponential_moving_average(
+    series: Iter<'_, Datum>,
+    days: usize,
+) -> Vec<(DateTime<Utc>, f64)> {
+    let window = window_len(series.clone(), days);
+    #[allow(clippy::cast_precision_loss)]
+    let alpha = 2.0 / (window as f64 + 1.0);
+    let mut average: Option<f64> = None;
+    series
+        .filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+        .map(|(timestamp, price)| {
+            let next = average.map_or(price, |average| average + alpha * (price - average));
+            average = Some(next);
+            (timestamp, next)
+        })
+        .collect()
+}
+
+/// Computes the mean and the band of two population standard deviations
+/// around it over the valid prices of the last `days` days, see
+/// [`window_len`]. Both start at the end of the first full window.
+#[must_use]
+pub fn bollinger_bands(
+    series: Iter<'_, Datum>,
+    days: usize,
+) -> (Vec<(DateTime<Utc>, f64)>, BandPoints) {
+    let window = window_len(series.clone(), days);
+    let prices: Vec<_> = series
+        .filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+        .collect();
+
+    #[allow(clippy::cast_precision_loss)]
+    let n = window as f64;
+    prices
+        .windows(window)
+        .map(|window| {
+            let timestamp = window[window.len() - 1].0;
+            let mean = window.iter().map(|(_, price)| price).sum::<f64>() / n;
+            let variance = window
+                .iter()
+                .map(|(_, price)| (price - mean).powi(2))
+                .sum::<f64>()
+                / n;
+            let width = 2.0 * variance.sqrt();
+            ((timestamp, mean), (timestamp, mean - width, mean + width))
+        })
+        .unzip()
+}
+
+/// Computes Wilder's Relative Strength Index over the changes between valid
+/// prices of the last `days` days, see [`window_len`]. It starts once the
+/// first period of changes is known.
+#[must_use]
+pub fn relative_strength_index(series: Iter<'_, Datum>, days: usize) -> Vec<(DateTime<Utc>, f64)> {
+    let period = window_len(series.clone(), days);
+    let prices: Vec<_> = series
+        .filter_map(|x| x.price().map(|price| (*x.timestamp(), price)))
+        .collect();
+
+    #[allow(clippy::cast_precision_loss)]
+    let n = period as f64;
+    let rsi = |gain: f64, loss: f64| {
+        if loss == 0.0 {
+            100.0
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        }
+    };
+
+    let mut gain = 0.0;
+    let mut loss = 0.0;
+    let mut index = Vec::with_capacity(prices.len().saturating_sub(period));
+    for (i, pair) in prices.windows(2).enumerate() {
+        let change = pair[1].1 - pair[0].1;
+        let (up, down) = (change.max(0.0), (-change).max(0.0));
+        if i < period {
+            // the first averages are plain means
+            gain += up / n;
+            loss += down / n;
+            if i + 1 < period {
+                continue;
+            }
+        } else {
+            gain = (gain * (n - 1.0) + up) / n;
+            loss = (loss * (n - 1.0) + down) / n;
+        }
+        index.push((pair[1].0, rsi(gain, loss)));
+    }
+
+    index
+}
+
+/// Which way a fast moving average crosses a slow one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cross {
+    /// Upwards.
+    Golden,
+    /// Downwards.
+    Death,
+}
+
+impl Cross {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Golden => "golden cross",
+            Self::Death => "death cross",
+        }
+    }
+}
+
+/// A point where two moving averages cross, see [`crossovers`].
+#[derive(Debug, PartialEq)]
+pub struct Crossover {
+    /// Time and value of the slow average where it is crossed.
+    pub point: (DateTime<Utc>, f64),
+    pub kind: Cross,
+}
+
+/// Where the `fast` average crosses the `slow` one, both as computed by
+/// [`simple_moving_average`] over the same prices. They are only compared
+/// once both are defined, so the warm-up of the slow one yields no
+/// crossover, and averages that meet without crossing don't count.
+#[must_use]
+pub fn crossovers(fast: &[(DateTime<Utc>, f64)], slow: &[(DateTime<Utc>, f64)]) -> Vec<Crossover> {
+    // both end at the last price, the slow one starts later
+    let fast = &fast[fast.len().saturating_sub(slow.len())..];
+    let mut above = None;
+    let mut crossovers = Vec::new();
+    for (&(_, fast), &(when, slow)) in fast.iter().zip(slow) {
+        let now_above = match fast.partial_cmp(&slow) {
+            Some(std::cmp::Ordering::Greater) => true,
+            Some(std::cmp::Ordering::Less) => false,
+            _ => continue,
+        };
+        if above.is_some_and(|above| above != now_above) {
+            crossovers.push(Crossover {
+                point: (when, slow),
+                kind: if now_above {
+                    Cross::Golden
+                } else {
+                    Cross::Death
+                },
+            });
+        }
+        above = Some(now_above);
+    }
+    crossovers
+}
+
+/// Largest decline from a peak to a later trough of a series, see
+/// [`Drawdown::of`].
+#[derive(Debug, PartialEq)]
+pub struct Drawdown {
+    /// Value and time of the peak.
+    pub peak: (DateTime<Utc>, f64),
+    /// Value and time of the lowest value after the peak.
+    pub trough: (DateTime<Utc>, f64),
+    /// Decline from `peak` to `trough` in percent of `peak`, positive.
+    pub percent: f64,
+}
+
+impl Drawdown {
+    /// Finds the largest drawdown of `series` in a single pass, ignoring
+    /// missing values and peaks that aren't positive. Returns `None` if
+    /// the values never decline.
+    pub fn of<'a>(series: impl IntoIterator<Item = &'a Datum>) -> Option<Self> {
+        let mut values = series
+            .into_iter()
+            .filter_map(|datum| datum.1.map(|value| (datum.0, value)));
+        let mut peak = values.next()?;
+        let mut largest: Option<Self> = None;
+        for (timestamp, value) in values {
+            if value > peak.1 {
+                peak = (timestamp, value);
+                continue;
+            }
+            if peak.1 <= 0.0 {
+                continue;
+            }
+            let percent = (peak.1 - value) / peak.1 * 100.0;
+            if percent > largest.as_ref().map_or(0.0, |largest| largest.percent) {
+                largest = Some(Self {
+                    peak,
+                    trough: (timestamp, value),
+                    percent,
+                });
+            }
+        }
+        largest
+    }
+}
+
+/// Fits a least-squares line through the values of `series` over their
+/// timestamps in seconds, ignoring missing values. Returns the slope per
+/// second and the value at the Unix epoch, or `None` if there aren't two
+/// different timestamps to fit a line through.
+// seconds since the epoch and counts of values are exact in f64 for ages
+#[allow(clippy::cast_precision_loss)]
+pub fn linear_regression<'a>(series: impl IntoIterator<Item = &'a Datum>) -> Option<(f64, f64)> {
+    let points: Vec<(f64, f64)> = series
+        .into_iter()
+        .filter_map(|datum| datum.1.map(|value| (datum.0.timestamp() as f64, value)))
+        .collect();
+    if points.is_empty() {
+        return None;
+    }
+
+    // centered on the means, squaring seconds since the epoch would lose
+    // the precision needed
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut sxx, mut sxy) = (0.0, 0.0);
+    for (x, y) in &points {
+        sxx += (x - mean_x) * (x - mean_x);
+        sxy += (x - mean_x) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Day-over-day changes in percent of the last value of each UTC day of
+/// `series`, ignoring missing values. A day without any value has no return,
+/// and neither has the day after it: a change over several days doesn't
+/// count as a daily one, so each missing day drops two returns.
+pub fn daily_returns<'a>(series: impl IntoIterator<Item = &'a Datum>) -> Vec<f64> {
+    returns_by_day(series)
+        .into_iter()
+        .map(|(_, change)| change)
+        .collect()
+}
+
+/// The [`daily_returns`] of `series` with the days they end on.
+fn returns_by_day<'a>(series: impl IntoIterator<Item = &'a Datum>) -> Vec<(NaiveDate, f64)> {
+    let mut closes: Vec<(NaiveDate, f64)> = Vec::new();
+    for datum in series {
+        let Some(value) = datum.1 else {
+            continue;
+        };
+        let day = datum.0.date_naive();
+        match closes.last_mut() {
+            Some((last, close)) if *last == day => *close = value,
+            _ => closes.push((day, value)),
+        }
+    }
+
+    closes
+        .windows(2)
+        .filter(|pair| pair[0].0.succ_opt() == Some(pair[1].0) && pair[0].1 != 0.0)
+        .map(|pair| (pair[1].0, (pair[1].1 - pair[0].1) / pair[0].1 * 100.0))
+        .collect()
+}
+
+/// A `tolerance` for [`align`] wide enough for the few seconds or minutes the
+/// timestamps of different coins differ by, narrow enough not to skip a
+/// value.
+pub const PAIRING_TOLERANCE: chrono::Duration = chrono::Duration::minutes(2);
+
+/// Pairs the values of `a` and `b` whose timestamps are at most `tolerance`
+/// apart, each value at most once, ignoring missing values. Both series must
+/// be sorted by time, as parsed. The pairs carry the timestamps of `a`.
+pub fn align<'a>(
+    a: impl IntoIterator<Item = &'a Datum>,
+    b: impl IntoIterator<Item = &'a Datum>,
+    tolerance: chrono::Duration,
+) -> Vec<(DateTime<Utc>, f64, f64)> {
+    let values = |series: &mut dyn Iterator<Item = &'a Datum>| {
+        series
+            .filter_map(|datum| datum.1.map(|value| (datum.0, value)))
+            .collect::<Vec<_>>()
+    };
+    let a = values(&mut a.into_iter());
+    let b = values(&mut b.into_iter());
+
+    let mut aligned = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while let (Some(&(a_time, a_value)), Some(&(b_time, b_value))) = (a.get(i), b.get(j)) {
+        if (a_time - b_time).abs() <= tolerance {
+            aligned.push((a_time, a_value, b_value));
+            i += 1;
+            j += 1;
+        } else if a_time < b_time {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    aligned
+}
+
+/// The values of `a` divided by those of `b` they are paired up with by
+/// [`align`] within `tolerance`, at the timestamps of `a`. Values divided by
+/// zero are left out.
+pub fn ratio<'a>(
+    a: impl IntoIterator<Item = &'a Datum>,
+    b: impl IntoIterator<Item = &'a Datum>,
+    tolerance: chrono::Duration,
+) -> Vec<Datum> {
+    align(a, b, tolerance)
+        .into_iter()
+        .filter(|&(_, _, b)| b != 0.0)
+        .map(|(timestamp, a, b)| Datum(timestamp, Some(a / b)))
+        .collect()
+}
+
+/// How closely the daily returns of two series move together, see
+/// [`Correlation::of`].
+#[derive(Debug, PartialEq)]
+pub struct Correlation {
+    /// Pearson correlation coefficient, from -1 to 1.
+    pub coefficient: f64,
+    /// Number of days with a return of both series.
+    pub days: usize,
+}
+
+impl Correlation {
+    /// Correlates the [`daily_returns`] of the values of `a` and `b` that are
+    /// paired up by [`align`] within `tolerance`, so that both returns of a
+    /// day are computed over the same times. Returns `None` for fewer than two
+    /// common returns or returns that never change.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn of<'a>(
+        a: impl IntoIterator<Item = &'a Datum>,
+        b: impl IntoIterator<Item = &'a Datum>,
+        tolerance: chrono::Duration,
+    ) -> Option<Self> {
+        let aligned = align(a, b, tolerance);
+        let side = |value: fn(&(DateTime<Utc>, f64, f64)) -> f64| -> Vec<Datum> {
+            aligned
+                .iter()
+                .map(|point| Datum(point.0, Some(value(point))))
+                .collect()
+        };
+        let b_returns: BTreeMap<_, _> =
+            returns_by_day(&side(|point| point.2)).into_iter().collect();
+        let returns: Vec<(f64, f64)> = returns_by_day(&side(|point| point.1))
+            .into_iter()
+            .filter_map(|(day, a)| b_returns.get(&day).map(|&b| (a, b)))
+            .collect();
+        if returns.len() < 2 {
+            return None;
+        }
+
+        let n = returns.len() as f64;
+        let mean_a = returns.iter().map(|(a, _)| a).sum::<f64>() / n;
+        let mean_b = returns.iter().map(|(_, b)| b).sum::<f64>() / n;
+        let (mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0);
+        for (a, b) in &returns {
+            saa += (a - mean_a) * (a - mean_a);
+            sbb += (b - mean_b) * (b - mean_b);
+            sab += (a - mean_a) * (b - mean_b);
+        }
+        if saa == 0.0 || sbb == 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            coefficient: sab / (saa * sbb).sqrt(),
+            days: returns.len(),
+        })
+    }
+}
+
+/// Days a year crypto currencies trade on, by which daily returns are
+/// annualized.
+pub const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Annualized risk and return of daily returns, see [`Sharpe::of`].
+#[derive(Debug, PartialEq)]
+pub struct Sharpe {
+    /// Excess return over the risk-free rate per unit of volatility.
+    pub ratio: f64,
+    /// Standard deviation of the returns in percent, annualized.
+    pub volatility: f64,
+}
+
+impl Sharpe {
+    /// Annualizes the mean and the sample standard deviation of the daily
+    /// `returns` in percent by [`DAYS_PER_YEAR`], and compares the mean with
+    /// the annual `risk_free` rate in percent. Returns `None` for fewer than
+    /// two returns or returns that never change, which have no volatility to
+    /// speak of.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn of(returns: &[f64], risk_free: f64) -> Option<Self> {
+        if returns.len() < 2 {
+            return None;
+        }
+
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns
+            .iter()
+            .map(|value| (value - mean) * (value - mean))
+            .sum::<f64>()
+            / (n - 1.0);
+        let volatility = (variance * DAYS_PER_YEAR).sqrt();
+        if volatility == 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            ratio: (mean * DAYS_PER_YEAR - risk_free) / volatility,
+            volatility,
+        })
+    }
+}
+
+/// Counts of values in bins of equal width, see [`Histogram::of`].
+#[derive(Debug, PartialEq)]
+pub struct Histogram {
+    /// Spanned by the bins, centered on zero.
+    pub range: Range<f64>,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Bins `values` into `bins` bins from minus to plus the largest absolute
+    /// value. Returns `None` if there are no values or no bins.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    #[must_use]
+    pub fn of(values: &[f64], bins: usize) -> Option<Self> {
+        if values.is_empty() || bins == 0 {
+            return None;
+        }
+
+        let largest = values
+            .iter()
+            .fold(0.0, |largest: f64, value| largest.max(value.abs()));
+        // a single bin for values that are all zero
+        let largest = if largest > 0.0 { largest } else { 1.0 };
+        let mut histogram = Self {
+            range: -largest..largest,
+            counts: vec![0; bins],
+        };
+        let width = histogram.bin_width();
+        for value in values {
+            // the largest value is the upper end of the last bin
+            let bin = (((value + largest) / width) as usize).min(bins - 1);
+            histogram.counts[bin] += 1;
+        }
+        Some(histogram)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn bin_width(&self) -> f64 {
+        (self.range.end - self.range.start) / self.counts.len() as f64
+    }
+}
+
+/// The instant `time` is in `timezone`, the earlier one if the clocks are
+/// turned back over it, and an hour later if they are turned forward over it.
+#[must_use]
+pub fn local(time: NaiveDateTime, timezone: Tz) -> DateTime<Utc> {
+    timezone
+        .from_local_datetime(&time)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(time + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map_or_else(|| time.and_utc(), |time| time.with_timezone(&Utc))
+}
+
+/// The weekends within `x_range`, from midnight in `timezone` before
+/// Saturday to midnight after Sunday, cut to the range. None for ranges
+/// shorter than two days, where shading would hide more than it shows.
+#[must_use]
+pub fn weekends(x_range: &Range<DateTime<Utc>>, timezone: Tz) -> Vec<Range<DateTime<Utc>>> {
+    if x_range.end - x_range.start < chrono::Duration::days(2) {
+        return Vec::new();
+    }
+
+    let midnight = |day: NaiveDate| local(day.and_time(NaiveTime::MIN), timezone);
+    let first_day = x_range.start.with_timezone(&timezone).date_naive();
+    let mut saturday = first_day
+        - chrono::Duration::days(
+            (i64::from(first_day.weekday().num_days_from_monday()) - 5).rem_euclid(7),
+        );
+    let mut weekends = Vec::new();
+    while midnight(saturday) < x_range.end {
+        let start = midnight(saturday);
+        let end = midnight(saturday + chrono::Duration::days(2));
+        weekends.push(start.max(x_range.start)..end.min(x_range.end));
+        saturday += chrono::Duration::days(7);
+    }
+    weekends.retain(|weekend| weekend.start < weekend.end);
+    weekends
+}
+
+/// Reduces `points` to `threshold` of them with the Largest-Triangle-Three-
+/// Buckets algorithm, which keeps the visual shape of the line. The first
+/// and the last point are always kept. Returns `points` unchanged if there
+/// are no more than `threshold` of them or `threshold` is below 3.
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn downsample(points: &[(DateTime<Utc>, f64)], threshold: usize) -> Vec<(DateTime<Utc>, f64)> {
+    let n = points.len();
+    if threshold >= n || threshold < 3 {
+        return points.to_vec();
+    }
+
+    let x = |i: usize| (points[i].0 - points[0].0).num_milliseconds() as f64;
+    // the points between the first and the last one go into equally sized
+    // buckets, one point is picked from each of them
+    let bucket_size = (n - 2) as f64 / (threshold - 2) as f64;
+    let bucket = |i: usize| {
+        let start = (i as f64 * bucket_size) as usize + 1;
+        let end = (((i + 1) as f64 * bucket_size) as usize + 1).min(n - 1);
+        start..end
+    };
+
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(points[0]);
+    let mut previous = 0;
+    for i in 0..threshold - 2 {
+        // the third corner is the average of the next bucket
+        let next = if i + 1 < threshold - 2 {
+            bucket(i + 1)
+        } else {
+            n - 1..n
+        };
+        let len = next.len() as f64;
+        let next_x = next.clone().map(x).sum::<f64>() / len;
+        let next_y = points[next].iter().map(|point| point.1).sum::<f64>() / len;
+
+        let (previous_x, previous_y) = (x(previous), points[previous].1);
+        let area = |j: usize| {
+            ((previous_x - next_x) * (points[j].1 - previous_y)
+                - (previous_x - x(j)) * (next_y - previous_y))
+                .abs()
+        };
+        // there are fewer buckets than points, so each holds at least one
+        let picked = bucket(i)
+            .max_by(|&a, &b| area(a).total_cmp(&area(b)))
+            .unwrap_or(previous);
+        sampled.push(points[picked]);
+        previous = picked;
+    }
+    sampled.push(points[n - 1]);
+
+    sampled
+}
+
+/// Parses a `market_chart` response from `reader`, with its series sorted
+/// and deduplicated, see [`Data::normalize`].
+///
+/// # Errors
+///
+/// Fails if `reader` doesn't contain a valid response.
+pub fn parse<R: io::Read>(reader: R) -> Result<Data> {
+    let mut data: Data = serde_json::from_reader(reader)?;
+    data.normalize();
+    Ok(data)
+}
+
+/// Parses a response of the `coins/{id}/ohlc` endpoint, sorting the candles
+/// by time.
+///
+/// # Errors
+///
+/// Fails if `reader` doesn't contain a valid response.
+pub fn parse_ohlc<R: io::Read>(reader: R) -> Result<Vec<Candle>> {
+    let mut candles: Vec<Candle> = serde_json::from_reader(reader)?;
+    candles.sort_by_key(|candle| candle.timestamp);
+    Ok(candles)
+}
@@ -0,0 +1,125 @@
+//! Diagnostics on stderr, as many of them as `--quiet` and `--verbose` ask
+//! for. Errors are always printed.
+
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How much to print besides errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Nothing.
+    Quiet,
+    /// Warnings and what was asked for, e.g. statistics.
+    Normal,
+    /// Also what is fetched and cached.
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Tells whether messages of `verbosity` are printed.
+pub fn enabled(verbosity: Verbosity) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= verbosity as u8
+}
+
+/// Whether a [`Spinner`] is drawn on the last line of stderr.
+static SPINNING: AtomicBool = AtomicBool::new(false);
+
+/// Prints `args` as a line of its own, in place of a spinner if there is one,
+/// which is redrawn below on its next turn.
+pub fn print(args: fmt::Arguments<'_>) {
+    let mut stderr = io::stderr().lock();
+    if SPINNING.load(Ordering::Relaxed) {
+        let _ = write!(stderr, "\r\x1b[2K");
+    }
+    let _ = writeln!(stderr, "{args}");
+}
+
+/// Prints a warning, unless quiet.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Normal) {
+            $crate::log::print(format_args!("warning: {}", format_args!($($arg)*)));
+        }
+    };
+}
+
+/// Prints a message, unless quiet.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Normal) {
+            $crate::log::print(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Prints a message only if verbose.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Verbosity::Verbose) {
+            $crate::log::print(format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use {info, verbose, warning};
+
+/// Shows that something is going on until dropped, by turning a spinner in
+/// front of a message on stderr. Nothing is shown if quiet or if stderr isn't
+/// a terminal, so it never ends up in logs.
+pub struct Spinner {
+    /// Dropped to stop the spinner.
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(message: String) -> Self {
+        if !enabled(Verbosity::Normal) || !io::stderr().is_terminal() {
+            return Self {
+                stop: None,
+                thread: None,
+            };
+        }
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            // nothing for what is done within the first turn, e.g. cached
+            for frame in ['|', '/', '-', '\\'].into_iter().cycle() {
+                if stopped.recv_timeout(Duration::from_millis(100))
+                    != Err(RecvTimeoutError::Timeout)
+                {
+                    break;
+                }
+                let mut stderr = io::stderr().lock();
+                SPINNING.store(true, Ordering::Relaxed);
+                let _ = write!(stderr, "\r{frame} {message}");
+                let _ = stderr.flush();
+            }
+            if SPINNING.swap(false, Ordering::Relaxed) {
+                let _ = write!(io::stderr().lock(), "\r\x1b[2K");
+            }
+        });
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -3,191 +3,2157 @@
 #![warn(rust_2018_idioms)]
 #![warn(clippy::pedantic)]
 
-use std::error;
-use std::fs::File;
-use std::slice::Iter;
+mod annotations;
+mod cache;
+mod config;
+mod export;
+mod fingerprint;
+mod log;
+mod meta;
+mod source;
+mod terminal;
 
-use chrono::serde::ts_milliseconds;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use cache::Cache;
 use chrono::{DateTime, NaiveDate, Utc};
-use clap::{arg, crate_name, crate_version, Command};
-use plotters::backend::SVGBackend;
-use plotters::chart::ChartBuilder;
+use chrono_tz::Tz;
+use clap::builder::{PossibleValue, PossibleValuesParser, RangedU64ValueParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{
+    arg, crate_name, crate_version, value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command,
+    ValueEnum,
+};
+use config::Config;
+use eth_hist_plot::chart::{
+    plot, Annotation, Band, Change, Chart, Coin, Decorations, Font, Grid, LineOptions, Marker,
+    Metric, Overlay, PanelStyle, PanelStyles, PlotOptions, Ranges, SeriesStyle, Span,
+};
+use eth_hist_plot::days::{self, Ago, Days};
+use eth_hist_plot::labels::LabelStyle;
+use eth_hist_plot::theme::{self, Palette, Theme};
+use eth_hist_plot::{
+    bollinger_bands, crossovers, exponential_moving_average, local, relative_strength_index,
+    simple_moving_average, weekends, Aggregate, Candle, Correlation, Crossover, Data, Datum,
+    Drawdown, Error, Histogram, Period, PriceField, Sharpe, Stats, PAIRING_TOLERANCE,
+};
+use fingerprint::Fingerprint;
+use flate2::read::GzDecoder;
+use log::Verbosity;
+use plotters::backend::BitMapBackend;
 use plotters::drawing::IntoDrawingArea;
-use plotters::element::Circle;
-use plotters::series::{LineSeries, PointSeries};
-use plotters::style::{Color, FontTransform, IntoFont, TextStyle, BLACK, BLUE, RED, WHITE};
-use serde::Deserialize;
+use source::{Binance, CoinGecko, DataSource, Interval, Provider, COIN_LIST_KEY, COIN_LIST_TTL};
+
+/// Warns if the API likely doesn't return what's expected for `days`.
+fn warn_about_days(matches: &ArgMatches, days: Days) {
+    if !days.is_documented() {
+        log::warning!(
+            "CoinGecko documents days of 1, 7, 14, 30, 90, 180, 365 and max only, \
+             expect {} data for {days}",
+            days.granularity()
+        );
+    }
+    // the public API limits the history to a year
+    let long = match days {
+        Days::Count(days) => days > 365,
+        Days::Max => true,
+    };
+    if long
+        && matches!(matches.get_one("interval"), Some(Interval::Daily))
+        && matches.get_one::<String>("api-key").is_none()
+    {
+        log::warning!("daily data beyond 365 days may require the Pro API, see --api-key");
+    }
+}
+
+fn parse_percent(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(percent) if percent.is_finite() => Ok(percent),
+        _ => Err(format!("`{s}` is not a percentage")),
+    }
+}
+
+fn parse_factor(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(factor),
+        _ => Err(format!("`{s}` is not a positive number")),
+    }
+}
+
+fn parse_timezone(s: &str) -> Result<Tz, String> {
+    s.parse()
+        .map_err(|_| format!("`{s}` is not an IANA time zone, e.g. Europe/Berlin"))
+}
+
+/// Checks that `id` looks like a coin id, i.e. a non-empty
+/// lowercase slug such as `ethereum` or `usd-coin`.
+fn parse_coin_id(id: &str) -> Result<String, String> {
+    if id.is_empty() {
+        return Err("coin id must not be empty".to_owned());
+    }
+
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(format!(
+            "`{id}` is not a valid coin id, expected a lowercase slug like `ethereum`"
+        ));
+    }
+
+    Ok(id.to_owned())
+}
+
+/// Checks that `code` looks like a currency code such as `usd` or `eur` and
+/// normalizes it to lowercase, which is what the API expects.
+fn parse_currency(code: &str) -> Result<String, String> {
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!(
+            "`{code}` is not a valid currency code, expected something like `usd`"
+        ));
+    }
+
+    Ok(code.to_ascii_lowercase())
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|err| format!("`{s}` is not an RFC 3339 date and time: {err}"))
+}
+
+/// Parses an RFC 3339 date and time, a plain `YYYY-MM-DD` date, which
+/// stands for the start of that day, or its end if `end_of_day` is set, or a
+/// time ago such as `90d`, see [`Ago::parse`].
+fn parse_date_bound(s: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    parse_date_bound_in(s, end_of_day, Tz::UTC)
+}
+
+/// Like [`parse_date_bound`], with plain dates being days in `timezone`.
+fn parse_date_bound_in(s: &str, end_of_day: bool, timezone: Tz) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_milli_opt(23, 59, 59, 999)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        return Ok(local(time.expect("valid time"), timezone));
+    }
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+
+    // dates and times have separators, times ago don't
+    if s.contains(['-', ':']) {
+        return Err(format!(
+            "`{s}` is neither an RFC 3339 date and time nor a YYYY-MM-DD date"
+        ));
+    }
+    Ok(Ago::parse(s)?.before(Utc::now()))
+}
+
+/// Checks `ids` against the cached list of coins, if there is a recent one,
+/// so that typos fail before fetching and with the closest ids suggested.
+fn check_coin_ids(ids: &[String]) -> Result<(), Error> {
+    let Some(coins) = Cache::in_default_dir(COIN_LIST_TTL)
+        .and_then(|cache| cache.load_with(COIN_LIST_KEY, source::parse_coin_list))
+    else {
+        return Ok(());
+    };
+
+    for id in ids {
+        if coins.iter().any(|coin| coin.id == *id) {
+            continue;
+        }
+        let mut closest: Vec<_> = coins
+            .iter()
+            .map(|coin| (edit_distance(id, &coin.id), coin.id.as_str()))
+            .filter(|&(distance, _)| distance <= (id.len() / 3).max(2))
+            .collect();
+        closest.sort_unstable();
+        let suggestions: Vec<_> = closest
+            .iter()
+            .take(3)
+            .map(|(_, id)| format!("`{id}`"))
+            .collect();
+        let hint = if suggestions.is_empty() {
+            "see list-coins".to_owned()
+        } else {
+            format!("did you mean {}?", suggestions.join(", "))
+        };
+        return Err(Error::InvalidArgs(format!("unknown coin `{id}`, {hint}")));
+    }
+    Ok(())
+}
+
+/// Number of characters to insert, delete or substitute to turn `a` into
+/// `b`, the Levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // distances from the prefix of `a` so far to each prefix of `b`
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Turns a coin id into a name suitable for captions, e.g. `ethereum` into
+/// `Ethereum`.
+fn display_name(coin: &str) -> String {
+    let mut chars = coin.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
 
-#[derive(Debug, Deserialize)]
-struct Data {
-    prices: Vec<Datum>,
-    market_caps: Vec<Datum>,
-    total_volumes: Vec<Datum>,
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    Svg,
+    Png,
+    Html,
+    Terminal,
 }
 
-impl Data {
-    fn iter_prices(&self) -> Iter<'_, Datum> {
-        self.prices.iter()
+impl ValueEnum for Format {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Svg, Self::Png, Self::Html, Self::Terminal]
     }
 
-    fn iter_market_caps(&self) -> Iter<'_, Datum> {
-        self.market_caps.iter()
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Svg => PossibleValue::new("svg"),
+            Self::Png => PossibleValue::new("png"),
+            Self::Html => PossibleValue::new("html").help("a page with the SVG inline"),
+            Self::Terminal => PossibleValue::new("terminal").help("price panel only, on stdout"),
+        })
     }
+}
 
-    fn iter_total_volumes(&self) -> Iter<'_, Datum> {
-        self.total_volumes.iter()
+impl Format {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            ext if ext.eq_ignore_ascii_case("svg") => Some(Self::Svg),
+            ext if ext.eq_ignore_ascii_case("png") => Some(Self::Png),
+            ext if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                Some(Self::Html)
+            }
+            _ => None,
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct Datum(
-    #[serde(with = "ts_milliseconds")] DateTime<Utc>,
-    Option<f64>,
-);
+/// Prints the price of each coin at `when` to stdout, prefixed with its name
+/// if there are several.
+fn print_price_at(coins: &[Coin], when: DateTime<Utc>) -> Result<(), Error> {
+    for coin in coins {
+        let Some(price) = coin.data.price_at(when) else {
+            let mut prices = coin
+                .data
+                .iter_prices()
+                .filter(|datum| datum.price().is_some());
+            let range = match (prices.next(), prices.next_back()) {
+                (Some(first), last) => format!(
+                    ", which range from {} to {}",
+                    first.timestamp(),
+                    last.unwrap_or(first).timestamp()
+                ),
+                (None, _) => String::new(),
+            };
+            return Err(Error::InvalidArgs(format!(
+                "{when} is outside of the {} prices{range}",
+                coin.name
+            )));
+        };
 
-impl Datum {
-    fn timestamp(&self) -> &DateTime<Utc> {
-        &self.0
+        if coins.len() > 1 {
+            println!("{}: {price}", coin.name);
+        } else {
+            println!("{price}");
+        }
     }
+    Ok(())
+}
 
-    fn price(&self) -> Option<f64> {
-        self.1
+/// Returns `output`, or [`default_output`] if unset, after creating its
+/// directory.
+fn output_path(output: Option<&PathBuf>, extension: &str) -> Result<PathBuf, Error> {
+    let output = output.cloned().unwrap_or_else(|| default_output(extension));
+    create_parent_dir(&output)?;
+    Ok(output)
+}
+
+/// Where a chart drawn as `extension` goes without `--output`.
+fn default_output(extension: &str) -> PathBuf {
+    PathBuf::from(format!("graph.{extension}"))
+}
+
+/// Makes sure the directory `path` is going to be written to exists.
+fn create_parent_dir(path: &Path) -> Result<(), Error> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(parent).map_err(|err| {
+                Error::Io(io::Error::new(
+                    err.kind(),
+                    format!("cannot create directory {}: {err}", parent.display()),
+                ))
+            })
+        }
+        _ => Ok(()),
     }
 }
 
-fn main() -> Result<(), Box<dyn error::Error>> {
-    let matches = Command::new(crate_name!())
+fn cli() -> Command {
+    Command::new(crate_name!())
         .version(crate_version!())
-        .args(&[arg!(--fetch "fetch from API")])
-        .get_matches();
+        .args(fetch_args())
+        .args(&[
+            arg!(--config <PATH> "read defaults from the TOML file at PATH \
+                                  [default: eth_hist_plot/config.toml in the config directory]")
+            .value_parser(value_parser!(PathBuf)),
+            arg!(--coin <ID> "CoinGecko id of the coin to plot, repeat to compare several coins \
+                              [default: ethereum]")
+            .value_parser(parse_coin_id)
+            .action(ArgAction::Append),
+            arg!(--currency <CODE> "currency to quote prices in [default: usd]")
+                .value_parser(parse_currency),
+            arg!(--from <DATE> "only plot data from DATE on, RFC 3339, YYYY-MM-DD or a time \
+                                ago like 90d, 2w, 6m or 1y")
+            .value_parser(|s: &str| parse_date_bound(s, false)),
+            arg!(--to <DATE> "only plot data up to DATE, RFC 3339, YYYY-MM-DD or a time ago")
+                .value_parser(|s: &str| parse_date_bound(s, true)),
+            arg!(--"export-csv" <PATH> "also write the data to PATH as CSV")
+                .value_parser(value_parser!(PathBuf)),
+            arg!(--"export-json" <PATH> "also write the data to PATH as JSON")
+                .value_parser(value_parser!(PathBuf)),
+        ])
+        .args(output_args())
+        .args(content_args())
+        .args(summary_args())
+        .subcommand(
+            Command::new("price-at")
+                .about("print the price at a point in time instead of plotting")
+                .arg(
+                    arg!(<DATETIME> "RFC 3339 date and time or YYYY-MM-DD, interpolated \
+                                     between the prices around it")
+                    .value_parser(|s: &str| parse_date_bound(s, false)),
+                ),
+        )
+        .subcommand(
+            Command::new("list-coins")
+                .about("print the id, symbol and name of the coins CoinGecko knows about")
+                .arg(
+                    arg!([FILTER] "only print the coins whose id, symbol or name contains \
+                                    FILTER, ignoring case"),
+                ),
+        )
+        // required unless listing coins, see parse_args
+        .group(ArgGroup::new("origin").args(["fetch", "input"]))
+}
+
+/// Arguments on what is fetched and how.
+fn fetch_args() -> Vec<Arg> {
+    vec![
+        arg!(--fetch "fetch from API"),
+        arg!(--source <API> "API to fetch from, see the README on how coins map to \
+                             Binance symbols")
+        .value_parser(value_parser!(Provider))
+        .default_value("coingecko"),
+        arg!(--input <FILE> "read a saved API response from FILE, - for stdin")
+            .value_parser(value_parser!(PathBuf)),
+        arg!(--gzip "decompress the input, implied by a FILE ending in .gz").requires("input"),
+        arg!(--days <DAYS> "number of days of history to fetch, a time ago like 6m, or max, \
+                            the data is 5-minutely for 1, hourly up to 90, else daily \
+                            [default: max]")
+        .value_parser(days::parse),
+        arg!(--"since-genesis" "fetch the whole history of the coins, as --days max")
+            .visible_alias("all")
+            .conflicts_with("days"),
+        arg!(--"cache-ttl" <SECONDS> "reuse fetched responses younger than SECONDS")
+            .value_parser(value_parser!(u64))
+            .default_value("3600"),
+        arg!(--"no-cache" "always fetch from the API"),
+        arg!(--offline "never fetch from the API, fail unless cached, whatever the age")
+            .requires("fetch")
+            // clap lets --input stand in for --fetch, which conflicts with it
+            .conflicts_with_all(["input", "no-cache"]),
+        arg!(--"dry-run" "print the requests --fetch would send and their cache keys, \
+                          without sending them")
+        .requires("fetch"),
+        arg!(--interval <INTERVAL> "granularity of the data to fetch")
+            .value_parser(value_parser!(Interval))
+            .default_value("auto"),
+        arg!(--retries <N> "retry failed requests N times, waiting longer each time")
+            .value_parser(value_parser!(u32))
+            .default_value("3"),
+        arg!(--timeout <SECONDS> "give up on connecting or reading after SECONDS")
+            .value_parser(RangedU64ValueParser::<u64>::new().range(1..))
+            .default_value("30"),
+        arg!(--"api-url" <URL> "fetch from the --source API at URL instead, up to the \
+                                version, e.g. from a mirror"),
+        arg!(--proxy <URL> "send requests through the proxy at URL, e.g. \
+                            http://proxy:3128 [default: from HTTPS_PROXY or HTTP_PROXY \
+                            unless NO_PROXY lists the host]")
+        .value_parser(|s: &str| {
+            source::parse_proxy(s)
+                .map(|_| s.to_owned())
+                .map_err(|err| err.to_string())
+        }),
+        arg!(--"user-agent" <STRING> "User-Agent header to identify the requests with")
+            .default_value(concat!(crate_name!(), "/", crate_version!())),
+        arg!(--"api-key" <KEY> "use the CoinGecko Pro API with KEY")
+            .env("COINGECKO_API_KEY")
+            .hide_env_values(true),
+        arg!(--concurrency <N> "fetch up to N coins at the same time")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+            .default_value("4"),
+    ]
+}
 
-    let data: Data = if matches.contains_id("fetch") {
-        let resp = ureq::get("https://api.coingecko.com/api/v3/coins/ethereum/market_chart")
-            .set("accept", "application/json")
-            .query("vs_currency", "usd")
-            .query("days", "max")
-            .call()?;
+/// Arguments on how and where the chart is written.
+fn output_args() -> Vec<Arg> {
+    vec![
+        arg!(--format <FORMAT> "output format, one for all --output or one for each \
+                                [default: inferred from --output, else svg]")
+        .value_parser(value_parser!(Format))
+        .action(ArgAction::Append),
+        arg!(--width <PIXELS> "width of the chart, in characters for the terminal \
+                               [default: 1024]")
+        .value_parser(RangedU64ValueParser::<u32>::new().range(1..)),
+        arg!(--height <PIXELS> "height of the chart, in lines for the terminal \
+                                [default: 768]")
+        .value_parser(RangedU64ValueParser::<u32>::new().range(1..)),
+        arg!(--theme <THEME> "colors of the chart")
+            .value_parser(value_parser!(Theme))
+            .default_value("light"),
+        arg!(--"price-color" <COLOR> "color of the prices of the first coin, e.g. #ff8800 \
+                                      [default: of the theme]")
+        .value_parser(theme::parse_color),
+        arg!(--"market-cap-color" <COLOR> "color of the market caps of the first coin \
+                                           [default: as the prices]")
+        .value_parser(theme::parse_color),
+        arg!(--"volume-color" <COLOR> "color of the volume of the first coin on its panel \
+                                       [default: as the prices]")
+        .value_parser(theme::parse_color),
+        arg!(--"price-label" <TEXT> "caption of the price panel [default: e.g. Ethereum price]"),
+        arg!(--"market-cap-label" <TEXT> "caption of the market cap panel \
+                                          [default: e.g. Ethereum market cap]"),
+        arg!(--"volume-label" <TEXT> "caption of the volume panel \
+                                      [default: e.g. Ethereum volume]"),
+        arg!(--"annotation-color" <COLOR> "color of the annotation and the vertical lines \
+                                           [default: of the theme]")
+        .value_parser(theme::parse_color),
+        arg!(--"y-label-style" <STYLE> "how to write the values on the y axes")
+            .value_parser(value_parser!(LabelStyle))
+            .default_value("plain"),
+        arg!(--"price-label-style" <STYLE> "how to write the prices on the y axis \
+                                            [default: as --y-label-style]")
+        .value_parser(value_parser!(LabelStyle)),
+        arg!(--"x-labels" <N> "label the x axes and draw grid lines at about N points in time \
+                               [default: to fit the width]")
+        .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--"y-labels" <N> "label the y axes and draw grid lines at about N values \
+                               [default: as plotters picks]")
+        .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--"no-grid" "don't draw grid lines, just the labels on the axes"),
+        arg!(--font <FAMILY> "font of all text, e.g. serif or a font installed such as Inter")
+            .default_value("sans-serif"),
+        arg!(--"font-scale" <FACTOR> "multiply the sizes of all text by FACTOR, e.g. 1.5 \
+                                      for presentations")
+        .value_parser(parse_factor)
+        .default_value("1"),
+        arg!(--timezone <TZ> "label dates in the IANA time zone TZ, e.g. Europe/Berlin, \
+                              with days starting at its midnight")
+        .value_parser(parse_timezone)
+        .default_value("UTC"),
+        arg!(--output <PATH> "file to write the chart to, - for stdout, repeat for several \
+                              [default: graph.svg]")
+        .value_parser(value_parser!(PathBuf))
+        .action(ArgAction::Append),
+        arg!(--minify "write the SVG without line breaks and default attributes, smaller but \
+                       harder to diff"),
+        arg!(--"skip-unchanged" "don't redraw the files of --output if drawn from the same \
+                                 data and options before, as kept next to them in .hash files"),
+        arg!(--"write-meta" <PATH> "also write to PATH as JSON what the chart was drawn from, \
+                                    the number of points and the extent of the axes")
+        .value_parser(value_parser!(PathBuf)),
+        arg!(--title <TEXT> "title to draw above all panels"),
+        arg!(--watermark "credit the source of the data in a corner of the chart \
+                          [default: if fetched]"),
+        arg!(--"no-watermark" "don't credit the source of fetched data")
+            .conflicts_with("watermark"),
+        arg!(-q --quiet "print nothing but errors"),
+        arg!(-v --verbose "also print what is fetched and cached").conflicts_with("quiet"),
+    ]
+}
 
-        serde_json::from_reader(resp.into_reader())?
+/// Arguments on what is printed besides the chart.
+fn summary_args() -> Vec<Arg> {
+    vec![
+        arg!(--stats "print a summary of the prices to stderr"),
+        arg!(--sharpe "print the annualized Sharpe ratio and volatility of the daily \
+                       returns to stderr"),
+        arg!(--correlation "print the correlation of the daily returns of the first coin \
+                            with those of the others to stderr"),
+        arg!(--"risk-free" <PERCENT> "annual risk-free rate of return for --sharpe")
+            .value_parser(parse_percent)
+            .default_value("0")
+            .requires("sharpe"),
+        arg!(--"check-gaps" [FACTOR] "warn about intervals between prices longer than \
+                                      FACTOR times the usual one [default: 3]")
+        .value_parser(parse_factor)
+        .default_missing_value("3"),
+        arg!(--strict "fail instead of warning if the prices, market caps and volumes \
+                       are at different times"),
+    ]
+}
+
+/// Arguments on what the chart shows and how its lines are drawn.
+fn content_args() -> Vec<Arg> {
+    vec![
+        arg!(--metric <METRIC> "plot a panel of METRIC, repeat for several panels \
+                                [default: all]")
+        .value_parser(value_parser!(Metric))
+        .action(ArgAction::Append),
+        arg!(--"no-volume" "don't plot the traded volume"),
+        arg!(--"volume-overlay" "draw the volume as bars behind the prices instead of \
+                                  on a panel of its own")
+        .conflicts_with("no-volume"),
+        arg!(--"volume-style" <STYLE> "how to draw the volume on its panel")
+            .value_parser(PossibleValuesParser::new(["line", "bar"]).map(|style| {
+                if style == "bar" {
+                    SeriesStyle::Bar
+                } else {
+                    SeriesStyle::Line
+                }
+            }))
+            .default_value("line")
+            .conflicts_with_all(["no-volume", "volume-overlay"]),
+        arg!(--style <STYLE> "how to draw the prices")
+            .value_parser(value_parser!(SeriesStyle))
+            .default_value("line"),
+        arg!(--"price-field" [FIELD] "plot the FIELD of each candle of the source as the \
+                                      prices [default: close]")
+        .value_parser(value_parser!(PriceField))
+        .default_missing_value("close"),
+        arg!(--candlestick "draw the prices as candles from the OHLC data of the API, \
+                            of a single coin")
+        .requires("fetch")
+        .conflicts_with_all(["normalize", "style"]),
+        arg!(--normalize "rebase prices to 100 at the start of the plotted range, labeled \
+                          as the change in percent"),
+        arg!(--ratio "plot the prices of the first of two coins in the second instead of both"),
+        arg!(--"fill-gaps" <MAX> "interpolate over up to MAX missing values in a row, \
+                                  breaking the lines at longer gaps")
+        .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--"max-points" <N> "draw lines through at most about N points, \
+                                 changing the shape as little as possible")
+        .value_parser(RangedU64ValueParser::<usize>::new().range(3..)),
+        arg!(--resample <PERIOD> "plot one value per calendar PERIOD, see --aggregate")
+            .value_parser(
+                PossibleValuesParser::new(["daily", "weekly", "monthly"]).map(
+                    |period| match period.as_str() {
+                        "daily" => Period::Daily,
+                        "weekly" => Period::Weekly,
+                        _ => Period::Monthly,
+                    },
+                ),
+            ),
+        arg!(--aggregate <HOW> "whether to plot the mean or the last value of each period")
+            .value_parser(PossibleValuesParser::new(["mean", "last"]).map(|how| {
+                if how == "last" {
+                    Aggregate::Last
+                } else {
+                    Aggregate::Mean
+                }
+            }))
+            .default_value("mean")
+            .requires("resample"),
+        arg!(--"zero-baseline" "start the price axis at zero").conflicts_with("log-scale"),
+        arg!(--"log-scale" "use a logarithmic y-axis for price and market cap, \
+                            ignored for panels containing non-positive values"),
+        arg!(--annotate <DATETIME> "mark the RFC 3339 DATETIME on every panel \
+                                    [default: the merge date for ethereum]")
+        .value_parser(parse_datetime),
+        arg!(--"no-annotation" "don't mark any point in time").conflicts_with("annotate"),
+        arg!(--vline <DATETIME> "draw a vertical line across the price panel at the \
+                                 RFC 3339 DATETIME, repeat for several lines")
+        .value_parser(parse_datetime)
+        .action(ArgAction::Append),
+        arg!(--annotations <FILE> "draw a vertical line for each event listed in FILE, \
+                                   JSON or TOML")
+        .value_parser(value_parser!(PathBuf)),
+        arg!(--sma <DAYS> "overlay a simple moving average over DAYS days of prices")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--ema <DAYS> "overlay an exponential moving average over DAYS days of prices")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--crossover <DAYS> "overlay simple moving averages over FAST and SLOW days of \
+                                 prices and mark and print where they cross")
+        .value_names(["FAST", "SLOW"])
+        .num_args(2)
+        .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        arg!(--rsi [DAYS] "add a panel with the RSI over DAYS days of prices [default: 14]")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+            .default_missing_value("14"),
+        arg!(--"returns-hist" "add a panel with a histogram of the daily returns in percent"),
+        arg!(--bins <N> "number of bars of the histogram of the returns")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+            .default_value("30")
+            .requires("returns-hist"),
+        arg!(--"mark-extremes" "mark the highest and the lowest price"),
+        arg!(--"show-change" "write the change from the first to the last price in a corner \
+                              of the price panel"),
+        arg!(--drawdown "shade the largest decline of the price and print it"),
+        arg!(--"shade-weekends" "shade Saturdays and Sundays, in UTC, on the price panel"),
+        arg!(--trendline "draw a least-squares line through the prices and print its slope"),
+        arg!(--bollinger <DAYS> "shade Bollinger Bands over DAYS days of prices")
+            .value_parser(RangedU64ValueParser::<usize>::new().range(2..)),
+    ]
+}
+
+/// Fetches the data of all `coins` or reads the one from the input file or
+/// stdin.
+fn load(
+    matches: &ArgMatches,
+    coins: &[String],
+    currency: &str,
+    days: Days,
+) -> Result<Vec<Data>, Error> {
+    if matches.get_flag("fetch") {
+        let source = data_source(matches)?;
+        let _spinner = log::Spinner::start(format!("fetching {}", coins.join(", ")));
+        let concurrency = *matches
+            .get_one::<usize>("concurrency")
+            .expect("has default");
+        let price_field = matches.get_one::<PriceField>("price-field").copied();
+        let results = fetch_all(coins, concurrency, |coin| {
+            let data = source.fetch(coin, currency, days)?;
+            match price_field {
+                Some(field) => Ok(with_prices(
+                    &data,
+                    &source.fetch_ohlc(coin, currency, days)?,
+                    field,
+                )),
+                None => Ok(data),
+            }
+        });
+
+        let mut data = Vec::with_capacity(coins.len());
+        let mut failures = Vec::new();
+        for (coin, result) in coins.iter().zip(results) {
+            match result {
+                Ok(result) => data.push(result),
+                Err(err) => failures.push((coin.clone(), err)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(data)
+        } else {
+            Err(Error::Fetch(failures))
+        }
+    } else if matches.contains_id("price-field") {
+        Err(Error::InvalidArgs(
+            "--price-field needs the candles of --fetch, saved responses have no open, \
+             high and low prices"
+                .to_owned(),
+        ))
     } else {
-        let file = File::open("/home/stephan/Downloads/response_1668851750741.json")?;
-        serde_json::from_reader(file)?
+        let input = matches
+            .get_one::<PathBuf>("input")
+            .expect("required by group");
+        let reader: Box<dyn Read> = if input.as_os_str() == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(File::open(input).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("cannot open {}: {err}", input.display()),
+                )
+            })?)
+        };
+        let data = if matches.get_flag("gzip")
+            || input.extension().is_some_and(|extension| extension == "gz")
+        {
+            eth_hist_plot::parse(GzDecoder::new(reader))?
+        } else {
+            eth_hist_plot::parse(reader)?
+        };
+        Ok(vec![data])
+    }
+}
+
+/// `data` with the `field` of each of `candles` as its prices.
+fn with_prices(data: &Data, candles: &[Candle], field: PriceField) -> Data {
+    let prices = candles
+        .iter()
+        .map(|candle| Datum::new(candle.timestamp, Some(candle.price(field))))
+        .collect();
+    Data::new(
+        prices,
+        data.iter_market_caps().cloned().collect(),
+        data.iter_total_volumes().cloned().collect(),
+    )
+}
+
+/// Prints the first request fetching each of `coins` would send, and the
+/// key of its response in the cache, if cached.
+fn dry_run(
+    matches: &ArgMatches,
+    coins: &[String],
+    currency: &str,
+    days: Days,
+) -> Result<(), Error> {
+    let source = data_source(matches)?;
+    let cached = !matches.get_flag("no-cache");
+    let mut plans: Vec<_> = coins
+        .iter()
+        .map(|coin| source.plan(coin, currency, days))
+        .collect();
+    if matches.get_flag("candlestick") || matches.contains_id("price-field") {
+        plans.extend(
+            coins
+                .iter()
+                .map(|coin| source.plan_ohlc(coin, currency, days)),
+        );
+    }
+
+    let mut stdout = io::stdout().lock();
+    for plan in plans {
+        writeln!(stdout, "GET {}", plan.url)?;
+        if cached {
+            writeln!(stdout, "  cached as {}", plan.key)?;
+        }
+    }
+    Ok(())
+}
+
+/// The source to fetch from as given on the command line.
+fn data_source(matches: &ArgMatches) -> Result<Box<dyn DataSource>, Error> {
+    let ttl = *matches.get_one::<u64>("cache-ttl").expect("has default");
+    let cache = cache(matches, Duration::from_secs(ttl))?;
+    Ok(match matches.get_one("source").expect("has default") {
+        Provider::CoinGecko => Box::new(coin_gecko(matches, cache)?),
+        Provider::Binance => {
+            let mut binance = Binance::new(
+                *matches.get_one::<u32>("retries").expect("has default"),
+                timeout(matches),
+                cache,
+                user_agent(matches),
+            );
+            if let Some(url) = matches.get_one::<String>("api-url") {
+                binance = binance.with_base_url(url.clone());
+            }
+            match proxy(matches, binance.base_url()) {
+                Some(proxy) => Box::new(binance.with_proxy(&proxy)?),
+                None => Box::new(binance),
+            }
+        }
+    })
+}
+
+/// The cache of responses younger than `ttl`, unless `--no-cache`, of the
+/// `--api-url` if given, and the only source of them with `--offline`.
+fn cache(matches: &ArgMatches, ttl: Duration) -> Result<Option<Cache>, Error> {
+    if matches.get_flag("no-cache") {
+        return Ok(None);
+    }
+    let mut cache = Cache::in_default_dir(ttl);
+    if let Some(url) = matches.get_one::<String>("api-url") {
+        cache = cache.map(|cache| cache.for_api(url));
+    }
+    if !matches.get_flag("offline") {
+        return Ok(cache);
+    }
+    cache.map(|cache| Some(cache.offline())).ok_or_else(|| {
+        Error::InvalidArgs("--offline needs a cache directory, but there is none".to_owned())
+    })
+}
+
+fn timeout(matches: &ArgMatches) -> Duration {
+    Duration::from_secs(*matches.get_one::<u64>("timeout").expect("has default"))
+}
+
+/// The [`CoinGecko`] API as configured on the command line, with `cache`.
+fn coin_gecko(matches: &ArgMatches, cache: Option<Cache>) -> Result<CoinGecko, Error> {
+    let mut coin_gecko = CoinGecko::new(
+        matches.get_one::<String>("api-key").cloned(),
+        *matches.get_one::<u32>("retries").expect("has default"),
+        timeout(matches),
+        *matches
+            .get_one::<Interval>("interval")
+            .expect("has default"),
+        cache,
+        user_agent(matches),
+    );
+    if let Some(url) = matches.get_one::<String>("api-url") {
+        coin_gecko = coin_gecko.with_base_url(url.clone());
+    }
+    match proxy(matches, coin_gecko.base_url()) {
+        Some(proxy) => coin_gecko.with_proxy(&proxy),
+        None => Ok(coin_gecko),
+    }
+}
+
+/// The proxy to send requests to `url` through: `--proxy`, else the one of
+/// `HTTPS_PROXY` or `HTTP_PROXY` for the scheme of `url`, in upper or lower
+/// case, unless `NO_PROXY` lists its host or a domain of it, or is `*`.
+fn proxy(matches: &ArgMatches, url: &str) -> Option<String> {
+    if let Some(proxy) = matches.get_one::<String>("proxy") {
+        return Some(proxy.clone());
+    }
+    let var = |name: &str| {
+        [name.to_ascii_uppercase(), name.to_owned()]
+            .into_iter()
+            .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
     };
+    let (scheme, rest) = url.split_once("://")?;
+    let proxy = var(&format!("{scheme}_proxy"))?;
 
-    let root = SVGBackend::new("graph.svg", (1024, 768)).into_drawing_area();
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host);
+    let excluded = var("no_proxy").is_some_and(|no_proxy| {
+        no_proxy.split(',').map(str::trim).any(|entry| {
+            let entry = entry.split(':').next().unwrap_or(entry);
+            let domain = entry.trim_start_matches('.');
+            entry == "*"
+                || (!domain.is_empty() && (host == domain || host.ends_with(&format!(".{domain}"))))
+        })
+    });
+    (!excluded).then_some(proxy)
+}
 
-    let sub_roots = root.split_evenly((2, 1));
+fn user_agent(matches: &ArgMatches) -> &str {
+    matches
+        .get_one::<String>("user-agent")
+        .expect("has default")
+}
 
-    let x_min = *data.iter_prices().map(Datum::timestamp).min().unwrap();
-    let x_max = *data.iter_prices().map(Datum::timestamp).max().unwrap();
-    let y_min = data
-        .iter_prices()
-        .filter_map(Datum::price)
-        .min_by(f64::total_cmp)
-        .unwrap();
-    let y_max = data
-        .iter_prices()
-        .filter_map(Datum::price)
-        .max_by(f64::total_cmp)
-        .unwrap();
-
-    let mut chart = ChartBuilder::on(&sub_roots[0])
-        .caption("ETH price", ("sans-serif", 50).into_font())
-        .margin(10)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
-
-    chart
-        .configure_mesh()
-        .x_label_style(
-            TextStyle::from(("sans-serif", 10).into_font()).transform(FontTransform::Rotate270),
+/// Prints the coins [`CoinGecko`] knows about, one per line with their ids,
+/// symbols and names separated by tabs, just the ones matching `filter` if
+/// given.
+fn list_coins(matches: &ArgMatches, filter: Option<&str>) -> Result<(), Error> {
+    let cache = cache(matches, COIN_LIST_TTL)?;
+    let coins = {
+        let _spinner = log::Spinner::start("fetching the list of coins".to_owned());
+        coin_gecko(matches, cache)?.coin_list()?
+    };
+
+    let filter = filter.map(str::to_lowercase);
+    let mut stdout = io::stdout().lock();
+    for coin in coins.iter().filter(|coin| {
+        filter.as_ref().is_none_or(|filter| {
+            [&coin.id, &coin.symbol, &coin.name]
+                .iter()
+                .any(|field| field.to_lowercase().contains(filter))
+        })
+    }) {
+        writeln!(stdout, "{}\t{}\t{}", coin.id, coin.symbol, coin.name)?;
+    }
+    Ok(())
+}
+
+/// Fetches the candles of the single coin in `coins` if asked for, within
+/// `--from` and `--to`.
+fn candles(
+    matches: &ArgMatches,
+    coins: &[String],
+    currency: &str,
+    days: Days,
+) -> Result<Option<Vec<Candle>>, Error> {
+    if !matches.get_flag("candlestick") {
+        return Ok(None);
+    }
+    let [coin] = coins else {
+        return Err(Error::InvalidArgs(
+            "--candlestick draws the candles of a single coin only".to_owned(),
+        ));
+    };
+
+    let spinner = log::Spinner::start(format!("fetching the {coin} candles"));
+    let mut candles = data_source(matches)?.fetch_ohlc(coin, currency, days)?;
+    drop(spinner);
+    let from = matches.get_one::<DateTime<Utc>>("from");
+    let to = matches.get_one::<DateTime<Utc>>("to");
+    candles.retain(|candle| {
+        from.is_none_or(|from| candle.timestamp >= *from)
+            && to.is_none_or(|to| candle.timestamp <= *to)
+    });
+    if candles.is_empty() {
+        return Err(Error::EmptyData(format!(
+            "no {coin} candles between --from and --to"
+        )));
+    }
+    Ok(Some(candles))
+}
+
+/// Calls `fetch` for every coin on at most `concurrency` threads and returns
+/// the results in the order of `coins`.
+fn fetch_all<T, F>(coins: &[String], concurrency: usize, fetch: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&str) -> T + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, T)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..concurrency.min(coins.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(coin) = coins.get(index) else {
+                            break results;
+                        };
+                        results.push((index, fetch(coin)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("fetching doesn't panic"))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn annotation(matches: &ArgMatches, coin: &str, timezone: Tz) -> Option<Annotation> {
+    if matches.get_flag("no-annotation") {
+        None
+    } else if let Some(&when) = matches.get_one::<DateTime<Utc>>("annotate") {
+        Some(Annotation {
+            when,
+            label: when
+                .with_timezone(&timezone)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+        })
+    } else if coin == "ethereum" {
+        Some(Annotation {
+            when: DateTime::<Utc>::from_naive_utc_and_offset(
+                NaiveDate::from_ymd_opt(2022, 9, 15)
+                    .expect("valid date")
+                    .and_hms_opt(6, 43, 0)
+                    .expect("valid time"),
+                Utc,
+            ),
+            label: "merge date".to_owned(),
+        })
+    } else {
+        None
+    }
+}
+
+fn load_config(matches: &ArgMatches) -> Result<Config, Error> {
+    match matches.get_one::<PathBuf>("config") {
+        Some(path) if !path.exists() => Err(Error::InvalidArgs(format!(
+            "config file {} doesn't exist",
+            path.display()
+        ))),
+        Some(path) => Config::load(path),
+        None => Ok(Config::default_path()
+            .map(|path| Config::load(&path))
+            .transpose()?
+            .unwrap_or_default()),
+    }
+}
+
+/// Validates the configured `value` of `key` with `parse`, falling back to
+/// `default` if it isn't configured.
+fn from_config(
+    value: Option<&str>,
+    key: &str,
+    parse: fn(&str) -> Result<String, String>,
+    default: &str,
+) -> Result<String, Error> {
+    value.map_or_else(
+        || Ok(default.to_owned()),
+        |value| parse(value).map_err(|err| Error::InvalidArgs(format!("config: {key}: {err}"))),
+    )
+}
+
+/// Resolves the `key` dimension of the canvas from the command line, the
+/// configuration or `default`, in this order.
+fn dimension(
+    matches: &ArgMatches,
+    key: &str,
+    configured: Option<u32>,
+    default: u32,
+) -> Result<u32, Error> {
+    if let Some(&value) = matches.get_one::<u32>(key) {
+        return Ok(value);
+    }
+    match configured {
+        Some(0) => Err(Error::InvalidArgs(format!(
+            "config: {key}: must be greater than zero"
+        ))),
+        value => Ok(value.unwrap_or(default)),
+    }
+}
+
+/// Writes the data to the files requested on the command line.
+fn export(matches: &ArgMatches, coins: &[Coin]) -> Result<(), Error> {
+    if let Some(path) = matches.get_one::<PathBuf>("export-csv") {
+        let [coin] = coins else {
+            return Err(Error::InvalidArgs(
+                "--export-csv supports a single coin only".to_owned(),
+            ));
+        };
+        create_parent_dir(path)?;
+        export::write_csv(path, &coin.data)?;
+    }
+
+    if let Some(path) = matches.get_one::<PathBuf>("export-json") {
+        let [coin] = coins else {
+            return Err(Error::InvalidArgs(
+                "--export-json supports a single coin only".to_owned(),
+            ));
+        };
+        create_parent_dir(path)?;
+        export::write_json(path, &coin.data)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+/// The ids of the coins to plot, from the command line or the `config`.
+fn coin_ids(matches: &ArgMatches, config: &Config) -> Result<Vec<String>, Error> {
+    let ids = match matches.get_many::<String>("coin") {
+        Some(ids) => ids.cloned().collect(),
+        None => vec![from_config(
+            config.coin.as_deref(),
+            "coin",
+            parse_coin_id,
+            "ethereum",
+        )?],
+    };
+    if ids.len() > 1 && !matches.get_flag("fetch") {
+        return Err(Error::InvalidArgs(
+            "comparing several coins requires --fetch".to_owned(),
+        ));
+    }
+    if ids.len() != 2 && matches.get_flag("ratio") {
+        return Err(Error::InvalidArgs("--ratio needs two coins".to_owned()));
+    }
+    if ids.len() < 2 && matches.get_flag("correlation") {
+        return Err(Error::InvalidArgs(
+            "--correlation needs at least two coins".to_owned(),
+        ));
+    }
+    Ok(ids)
+}
+
+/// The days to fetch from the command line, else from the config file.
+fn days(matches: &ArgMatches, config: &Config) -> Days {
+    if matches.get_flag("since-genesis") {
+        if matches.get_flag("fetch") {
+            log::warning!(
+                "the whole history is daily at best and takes longer to fetch, \
+                 see --days for less"
+            );
+        }
+        return Days::Max;
+    }
+    matches
+        .get_one::<Days>("days")
+        .copied()
+        .or(config.days)
+        .unwrap_or(Days::Max)
+}
+
+/// The currency from the command line, else from the config file.
+fn currency(matches: &ArgMatches, config: &Config) -> Result<String, Error> {
+    match matches.get_one::<String>("currency") {
+        Some(currency) => Ok(currency.clone()),
+        None => from_config(
+            config.currency.as_deref(),
+            "currency",
+            parse_currency,
+            "usd",
+        ),
+    }
+}
+
+fn verbosity(matches: &ArgMatches) -> Verbosity {
+    if matches.get_flag("quiet") {
+        Verbosity::Quiet
+    } else if matches.get_flag("verbose") {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    }
+}
+
+/// Parses the command line, failing like clap does if neither `--fetch` nor
+/// `--input` is given unless listing coins, which needs neither.
+fn parse_args() -> ArgMatches {
+    let mut cli = cli();
+    let matches = cli.get_matches_mut();
+    if !matches.contains_id("origin") && matches.subcommand_name() != Some("list-coins") {
+        cli.error(
+            ErrorKind::MissingRequiredArgument,
+            "one of --fetch or --input is required",
         )
-        .draw()?;
-
-    chart
-        .draw_series(LineSeries::new(
-            data.iter_prices()
-                .filter_map(|x| x.price().map(|price| (*x.timestamp(), price))),
-            RED,
-        ))?
-        .label("ETH price in USD");
-
-    let exact_merge_date = DateTime::<Utc>::from_naive_utc_and_offset(
-        NaiveDate::from_ymd_opt(2022, 9, 15)
-            .expect("valid date")
-            .and_hms_opt(6, 43, 0)
-            .expect("valid time"),
-        Utc,
-    );
-    let point_data = vec![(exact_merge_date, 1450f64)];
-
-    chart
-        .draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
-            point_data.iter().map(|x| (x.0, x.1)),
-            5,
-            BLUE,
-        ))?
-        .label("merge date");
-
-    chart
-        .configure_series_labels()
-        .background_style(WHITE.mix(0.8))
-        .border_style(BLACK)
-        .draw()?;
-
-    let x_min = *data.iter_market_caps().map(Datum::timestamp).min().unwrap();
-    let x_max = *data.iter_market_caps().map(Datum::timestamp).max().unwrap();
-    let y_min = data
-        .iter_market_caps()
-        .filter_map(Datum::price)
-        .min_by(f64::total_cmp)
-        .unwrap();
-    let y_max = data
-        .iter_market_caps()
-        .filter_map(Datum::price)
-        .max_by(f64::total_cmp)
-        .unwrap();
-
-    let mut chart = ChartBuilder::on(&sub_roots[1])
-        .caption("ETH market cap", ("sans-serif", 50).into_font())
-        .margin(10)
-        .margin_left(55)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
-
-    chart
-        .configure_mesh()
-        .x_label_style(
-            TextStyle::from(("sans-serif", 10).into_font()).transform(FontTransform::Rotate270),
+        .exit();
+    }
+    if crossover_windows(&matches).is_some_and(|(fast, slow)| fast >= slow) {
+        cli.error(
+            ErrorKind::ValueValidation,
+            "the FAST days of --crossover must be fewer than the SLOW ones",
+        )
+        .exit();
+    }
+    let outputs = matches
+        .get_many::<PathBuf>("output")
+        .map_or(0, Iterator::count);
+    let formats = matches
+        .get_many::<Format>("format")
+        .map_or(0, Iterator::count);
+    if formats > 1 && formats != outputs {
+        cli.error(
+            ErrorKind::WrongNumberOfValues,
+            "give one --format for all --output, or one for each",
         )
-        .draw()?;
-
-    chart
-        .draw_series(LineSeries::new(
-            data.iter_market_caps()
-                .filter_map(|x| x.price().map(|price| (*x.timestamp(), price))),
-            RED,
-        ))?
-        .label("ETH market cap in USD");
-
-    let point_data = vec![(exact_merge_date, 1450f64)];
-
-    chart
-        .draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
-            point_data.into_iter(),
-            5,
-            BLUE,
-        ))?
-        .label("merge date");
-
-    chart
-        .configure_series_labels()
-        .background_style(WHITE.mix(0.8))
-        .border_style(BLACK)
-        .draw()?;
-
-    root.present()?;
+        .exit();
+    }
+    let to_stdout = matches
+        .get_many::<PathBuf>("output")
+        .into_iter()
+        .flatten()
+        .filter(|path| path.as_os_str() == "-")
+        .count();
+    if to_stdout > 1 {
+        cli.error(ErrorKind::ArgumentConflict, "only one --output can be -")
+            .exit();
+    }
+    matches
+}
+
+/// The ranges of the panels, see [`Ranges::new`], warning about the volume
+/// panel if it is left out for lack of data.
+fn ranges(coins: &[Coin], candles: Option<&[Candle]>, metrics: &[Metric]) -> Result<Ranges, Error> {
+    let ranges = Ranges::new(coins, candles, metrics)?;
+    if metrics.contains(&Metric::Volume) && ranges.volume.is_none() {
+        log::warning!("no volume data, leaving out the volume panel");
+    }
+    Ok(ranges)
+}
+
+fn run() -> Result<(), Error> {
+    let matches = parse_args();
+    log::set_verbosity(verbosity(&matches));
+    if let Some(list) = matches.subcommand_matches("list-coins") {
+        return list_coins(
+            &matches,
+            list.get_one::<String>("FILTER").map(String::as_str),
+        );
+    }
+
+    let config = load_config(&matches)?;
+    // read before fetching, a mistake in the file is quick to fix
+    let events = match matches.get_one::<PathBuf>("annotations") {
+        Some(path) => annotations::load(path, timezone(&matches))?,
+        None => Vec::new(),
+    };
+
+    let ids = coin_ids(&matches, &config)?;
+    let currency = currency(&matches, &config)?;
+    let days = days(&matches, &config);
+    if matches.get_flag("fetch") && matches!(matches.get_one("source"), Some(Provider::CoinGecko)) {
+        warn_about_days(&matches, days);
+        if !matches.get_flag("no-cache") {
+            check_coin_ids(&ids)?;
+        }
+    }
+
+    if matches.get_flag("dry-run") {
+        return dry_run(&matches, &ids, &currency, days);
+    }
+    let mut options = plot_options(&matches, &config, &ids[0], &currency)?;
+
+    let candles = candles(&matches, &ids, &currency, days)?;
+    let mut coins: Vec<_> = ids
+        .iter()
+        .zip(load(&matches, &ids, &currency, days)?)
+        .map(|(id, data)| Coin {
+            name: display_name(id),
+            data,
+        })
+        .collect();
+
+    clean(&matches, &mut coins)?;
+
+    export(&matches, &coins)?;
+
+    if let Some(matches) = matches.subcommand_matches("price-at") {
+        let when = *matches.get_one("DATETIME").expect("required");
+        return print_price_at(&coins, when);
+    }
+
+    // of the actual prices, before any normalization
+    let stats = stats(&matches, &coins);
+
+    if matches.get_flag("sharpe") {
+        print_sharpe(&coins, *matches.get_one("risk-free").expect("has default"));
+    }
+    if matches.get_flag("correlation") {
+        print_correlations(&coins);
+    }
+    // of the coins as fetched, not of the ratio replacing them
+    let coin_meta = coin_meta(&matches, &ids, &currency, &coins, days)?;
+    ratio(&matches, &mut coins, &mut options);
+
+    if options.normalized {
+        for coin in &mut coins {
+            coin.data.index_prices();
+        }
+    }
+
+    // bail out before creating the output file
+    let mut ranges = ranges(&coins, candles.as_deref(), &options.metrics)?;
+    if matches.get_flag("zero-baseline") {
+        ranges.price.1.start = ranges.price.1.start.min(0.0);
+    }
+
+    let palette = &options.palette;
+    let mut decorations = decorations(&matches, &ids[0], &coins[0], &ranges.price.0, palette);
+    decorations.vlines.extend(events);
+
+    let chart = Chart {
+        coins,
+        ranges,
+        days: matches.get_flag("fetch").then_some(days),
+        decorations,
+        candles,
+        options,
+    };
+
+    let fingerprint = fingerprint(&matches, &config, &chart)?;
+    render(&matches, &config, &chart, fingerprint.as_deref())?;
+    write_meta(&matches, coin_meta, &currency, &chart)?;
+
+    for (name, stats) in stats {
+        match stats {
+            Some(stats) => log::info!("{name}: {}", summary(&stats, &chart.options.currency)),
+            None => log::info!("{name}: no prices"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the two `coins` by the prices of the first in the second, if
+/// asked for with `--ratio`, and `options` by those of a chart of them.
+fn ratio(matches: &ArgMatches, coins: &mut Vec<Coin>, options: &mut PlotOptions) {
+    if !matches.get_flag("ratio") {
+        return;
+    }
+    let (first, second) = (&coins[0], &coins[1]);
+    let prices = eth_hist_plot::ratio(
+        first.data.iter_prices(),
+        second.data.iter_prices(),
+        PAIRING_TOLERANCE,
+    );
+    let ratio = Coin {
+        name: first.name.clone(),
+        data: Data::new(prices, Vec::new(), Vec::new()),
+    };
+    // quoted in the second coin, which has no market cap or volume of that
+    options.currency.clone_from(&second.name);
+    options.metrics.retain(|metric| *metric == Metric::Price);
+    options.volume_overlay = false;
+    *coins = vec![ratio];
+}
+
+/// The statistics of the prices of each of `coins`, if asked for with
+/// `--stats`.
+fn stats(matches: &ArgMatches, coins: &[Coin]) -> Vec<(String, Option<Stats>)> {
+    if !matches.get_flag("stats") {
+        return Vec::new();
+    }
+    coins
+        .iter()
+        .map(|coin| (coin.name.clone(), Stats::of(coin.data.iter_prices())))
+        .collect()
+}
+
+/// Credit for the source of the data, by default only if fetched. Saved
+/// responses are taken to be from [`CoinGecko`].
+fn watermark(matches: &ArgMatches) -> Option<String> {
+    let fetched = matches.get_flag("fetch");
+    if matches.get_flag("no-watermark") || !(fetched || matches.get_flag("watermark")) {
+        return None;
+    }
+    let provider = if fetched {
+        *matches.get_one::<Provider>("source").expect("has default")
+    } else {
+        Provider::CoinGecko
+    };
+    Some(format!("Data: {}", provider.name()))
+}
+
+/// How to draw the chart of the coin `id` in `currency`, as given on the
+/// command line and in `config`.
+fn plot_options(
+    matches: &ArgMatches,
+    config: &Config,
+    id: &str,
+    currency: &str,
+) -> Result<PlotOptions, Error> {
+    let volume_overlay = matches.get_flag("volume-overlay");
+    let theme = *matches.get_one::<Theme>("theme").expect("has default");
+    Ok(PlotOptions {
+        name: display_name(id),
+        currency: currency.to_ascii_uppercase(),
+        size: (
+            dimension(matches, "width", config.width, 1024)?,
+            dimension(matches, "height", config.height, 768)?,
+        ),
+        title: matches.get_one::<String>("title").cloned(),
+        log_scale: matches.get_flag("log-scale"),
+        normalized: matches.get_flag("normalize"),
+        theme,
+        palette: palette(matches, theme),
+        watermark: watermark(matches),
+        volume_overlay,
+        metrics: metrics(matches, volume_overlay),
+        label_styles: label_styles(matches),
+        series_style: *matches.get_one("style").expect("has default"),
+        volume_style: *matches.get_one("volume-style").expect("has default"),
+        panel_styles: PanelStyles {
+            // colored with the palette, see palette()
+            price: PanelStyle {
+                color: None,
+                label: matches.get_one::<String>("price-label").cloned(),
+            },
+            market_cap: panel_style(matches, "market-cap"),
+            volume: panel_style(matches, "volume"),
+        },
+        line_options: LineOptions {
+            broken: matches.contains_id("fill-gaps"),
+            max_points: matches.get_one::<usize>("max-points").copied(),
+        },
+        grid: grid(matches),
+        font: font(matches),
+    })
+}
+
+fn grid(matches: &ArgMatches) -> Grid {
+    Grid {
+        x_labels: matches.get_one::<usize>("x-labels").copied(),
+        y_labels: matches.get_one::<usize>("y-labels").copied(),
+        lines: !matches.get_flag("no-grid"),
+        timezone: timezone(matches),
+    }
+}
+
+fn timezone(matches: &ArgMatches) -> Tz {
+    *matches.get_one("timezone").expect("has default")
+}
+
+fn font(matches: &ArgMatches) -> Font {
+    Font {
+        family: matches
+            .get_one::<String>("font")
+            .cloned()
+            .expect("has default"),
+        scale: *matches.get_one("font-scale").expect("has default"),
+    }
+}
+
+/// The colors of `theme`, with the ones given on the command line instead.
+fn palette(matches: &ArgMatches, theme: Theme) -> Palette {
+    let mut palette = *theme.palette();
+    if let Some(&color) = matches.get_one("price-color") {
+        palette.lines[0] = color;
+    }
+    if let Some(&color) = matches.get_one("annotation-color") {
+        palette.annotation = color;
+    }
+    palette
+}
+
+/// The color and the caption of the panel of `metric` as given on the command
+/// line.
+fn panel_style(matches: &ArgMatches, metric: &str) -> PanelStyle {
+    PanelStyle {
+        color: matches.get_one(&format!("{metric}-color")).copied(),
+        label: matches
+            .get_one::<String>(&format!("{metric}-label"))
+            .cloned(),
+    }
+}
+
+/// The panels to plot, leaving out the volume if not wanted or drawn on the
+/// price panel.
+fn metrics(matches: &ArgMatches, volume_overlay: bool) -> Vec<Metric> {
+    let mut metrics: Vec<Metric> = match matches.get_many::<Metric>("metric") {
+        Some(metrics) => metrics.copied().collect(),
+        None => Metric::value_variants().to_vec(),
+    };
+    if matches.get_flag("no-volume") || volume_overlay {
+        metrics.retain(|&metric| metric != Metric::Volume);
+    }
+    metrics
+}
+
+/// Returns the styles of the labels of the price axis and of the others.
+fn label_styles(matches: &ArgMatches) -> (LabelStyle, LabelStyle) {
+    let values = *matches
+        .get_one::<LabelStyle>("y-label-style")
+        .expect("has default");
+    let prices = matches
+        .get_one::<LabelStyle>("price-label-style")
+        .copied()
+        .unwrap_or(values);
+    (prices, values)
+}
+
+/// Checks that the series of `coins` line up, drops their non-finite values,
+/// restricts them to the requested range, warns about gaps in their prices,
+/// fills them and resamples them as requested.
+fn clean(matches: &ArgMatches, coins: &mut [Coin]) -> Result<(), Error> {
+    // the prices of candles are at times of their own, e.g. every 4 days
+    if !matches.contains_id("price-field") {
+        validate(matches, coins)?;
+    }
+
+    for coin in &mut *coins {
+        let dropped = coin.data.drop_non_finite();
+        if dropped > 0 {
+            log::warning!("ignoring {dropped} non-finite {} values", coin.name);
+        }
+    }
+
+    let from = matches.get_one::<DateTime<Utc>>("from").copied();
+    let to = matches.get_one::<DateTime<Utc>>("to").copied();
+    if from.is_some() || to.is_some() {
+        retain_between(coins, from, to)?;
+    }
+
+    if let Some(&factor) = matches.get_one::<f64>("check-gaps") {
+        for coin in &*coins {
+            for (start, end) in coin.data.price_gaps(factor) {
+                log::warning!(
+                    "no {} prices from {start} to {end}, for {}",
+                    coin.name,
+                    format_duration(end - start)
+                );
+            }
+        }
+    }
+
+    if let Some(&max_gap) = matches.get_one::<usize>("fill-gaps") {
+        for coin in &mut *coins {
+            coin.data.fill_gaps(max_gap);
+        }
+    }
+
+    if let Some(&period) = matches.get_one::<Period>("resample") {
+        let aggregate = *matches.get_one("aggregate").expect("has default");
+        for coin in coins {
+            coin.data.resample(period, aggregate);
+        }
+    }
+
     Ok(())
 }
+
+/// Warns if the series of any of `coins` are at different times, or fails
+/// with `--strict`.
+fn validate(matches: &ArgMatches, coins: &[Coin]) -> Result<(), Error> {
+    for coin in coins {
+        // CoinGecko's timestamps of the same point differ by milliseconds at
+        // most, anything further off is another point
+        match coin.data.validate(chrono::Duration::seconds(1)) {
+            Err(err) if matches.get_flag("strict") => {
+                return Err(Error::Inconsistent(format!("{}: {err}", coin.name)));
+            }
+            Err(err) => log::warning!("{}: {err}", coin.name),
+            Ok(()) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Formats `duration` in days and hours, or minutes if shorter.
+fn format_duration(duration: chrono::Duration) -> String {
+    let (days, hours) = (duration.num_days(), duration.num_hours() % 24);
+    match (days, hours) {
+        (0, 0) => format!("{} minutes", duration.num_minutes()),
+        (0, hours) => format!("{hours} hours"),
+        (days, 0) => format!("{days} days"),
+        (days, hours) => format!("{days} days {hours} hours"),
+    }
+}
+
+/// Formats `stats` of prices in `currency` as one line.
+fn summary(stats: &Stats, currency: &str) -> String {
+    let date = |timestamp: DateTime<Utc>| timestamp.format("%Y-%m-%d");
+    format!(
+        "min {:.2} {currency} on {}, max {:.2} {currency} on {}, mean {:.2} {currency}, \
+         start {:.2} {currency}, end {:.2} {currency}, change {:+.2}%",
+        stats.min.1,
+        date(stats.min.0),
+        stats.max.1,
+        date(stats.max.0),
+        stats.mean,
+        stats.start,
+        stats.end,
+        stats.change,
+    )
+}
+
+/// Prints the Sharpe ratio and the volatility of the daily returns of each
+/// coin over the `risk_free` rate in percent.
+fn print_sharpe(coins: &[Coin], risk_free: f64) {
+    for coin in coins {
+        let returns = eth_hist_plot::daily_returns(coin.data.iter_prices());
+        match Sharpe::of(&returns, risk_free) {
+            Some(sharpe) => log::info!(
+                "{}: Sharpe ratio {:.2} over {risk_free}% risk-free, volatility {:.2}%, \
+                 annualized over {} days",
+                coin.name,
+                sharpe.ratio,
+                sharpe.volatility,
+                eth_hist_plot::DAYS_PER_YEAR,
+            ),
+            None => log::warning!(
+                "{} daily returns of {} are too few or too steady for a Sharpe ratio",
+                returns.len(),
+                coin.name
+            ),
+        }
+    }
+}
+
+/// Prints the correlation of the daily returns of the first of `coins` with
+/// those of each other one.
+fn print_correlations(coins: &[Coin]) {
+    let (first, others) = coins.split_first().expect("checked in coin_ids");
+    for other in others {
+        match Correlation::of(
+            first.data.iter_prices(),
+            other.data.iter_prices(),
+            PAIRING_TOLERANCE,
+        ) {
+            Some(correlation) => log::info!(
+                "{} and {}: correlation {:.2} of the daily returns over {} days",
+                first.name,
+                other.name,
+                correlation.coefficient,
+                correlation.days
+            ),
+            None => log::warning!(
+                "too few common daily returns of {} and {} for a correlation",
+                first.name,
+                other.name
+            ),
+        }
+    }
+}
+
+/// Plots `chart` as SVG, minified if `minify`.
+fn svg(chart: &Chart, minify: bool) -> Result<String, Error> {
+    let svg = chart.svg()?;
+    Ok(if minify { minify_svg(&svg) } else { svg })
+}
+
+/// Strips `svg` as written by plotters of the line breaks and of attributes
+/// set to their defaults. Text is on lines of its own, tags are never split
+/// across lines and there are no groups to inherit attributes from.
+fn minify_svg(svg: &str) -> String {
+    let mut minified = String::with_capacity(svg.len());
+    for line in svg.lines().map(str::trim) {
+        if line.starts_with('<') {
+            minified.push_str(
+                &line
+                    .replace(" opacity=\"1\"", "")
+                    .replace(" stroke-width=\"1\"", ""),
+            );
+        } else {
+            minified.push_str(line);
+        }
+    }
+    minified
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents).map_err(|err| {
+        Error::Io(io::Error::new(
+            err.kind(),
+            format!("cannot write {}: {err}", path.display()),
+        ))
+    })
+}
+
+/// Writes a page showing `chart` as SVG to `output`, or to stdout if unset.
+fn write_html(chart: &Chart, minify: bool, output: Option<&Path>) -> Result<(), Error> {
+    let page = html_page(&chart.title(), &svg(chart, minify)?);
+    match output {
+        Some(output) => write_file(output, &page),
+        None => Ok(io::stdout().lock().write_all(page.as_bytes())?),
+    }
+}
+
+/// A standalone HTML page titled `title` with the `svg` inline, no scripts or
+/// anything else to load.
+fn html_page(title: &str, svg: &str) -> String {
+    let svg = svg.trim_end();
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n\
+         </head>\n\
+         <body>\n\
+         {svg}\n\
+         </body>\n\
+         </html>\n"
+    )
+}
+
+/// Hashes what `chart` is drawn from if asked to with `--skip-unchanged`.
+fn fingerprint(
+    matches: &ArgMatches,
+    config: &Config,
+    chart: &Chart,
+) -> Result<Option<String>, Error> {
+    if !matches.get_flag("skip-unchanged") {
+        return Ok(None);
+    }
+    let mut fingerprint = Fingerprint::new(env::args_os());
+    fingerprint.add(format!("{config:?}").as_bytes());
+    for coin in &chart.coins {
+        fingerprint.add(coin.name.as_bytes());
+        fingerprint.add(&serde_json::to_vec(&coin.data)?);
+    }
+    if let Some(candles) = &chart.candles {
+        fingerprint.add(&serde_json::to_vec(candles)?);
+    }
+    // the annotations are read from a file
+    for vline in &chart.decorations.vlines {
+        fingerprint.add(vline.when.to_rfc3339().as_bytes());
+        fingerprint.add(vline.label.as_bytes());
+    }
+    Ok(Some(fingerprint.hex()))
+}
+
+/// Describes each of `coins` with the id of `ids` for the file of
+/// `--write-meta`, none if not given.
+fn coin_meta(
+    matches: &ArgMatches,
+    ids: &[String],
+    currency: &str,
+    coins: &[Coin],
+    days: Days,
+) -> Result<Vec<meta::CoinMeta>, Error> {
+    if !matches.contains_id("write-meta") {
+        return Ok(Vec::new());
+    }
+    ids.iter()
+        .zip(coins)
+        .map(|(id, coin)| {
+            Ok(meta::CoinMeta {
+                id: id.clone(),
+                fetched_at: if matches.get_flag("fetch") {
+                    Some(fetched_at(matches, id, currency, days)?)
+                } else {
+                    None
+                },
+                prices: coin.data.iter_prices().len(),
+                market_caps: coin.data.iter_market_caps().len(),
+                total_volumes: coin.data.iter_total_volumes().len(),
+            })
+        })
+        .collect()
+}
+
+/// Describes how `chart` of the `coins` in `currency` came about to the file
+/// of `--write-meta`, if given.
+fn write_meta(
+    matches: &ArgMatches,
+    coins: Vec<meta::CoinMeta>,
+    currency: &str,
+    chart: &Chart,
+) -> Result<(), Error> {
+    let Some(path) = matches.get_one::<PathBuf>("write-meta") else {
+        return Ok(());
+    };
+    let source = if matches.get_flag("fetch") {
+        let provider = matches.get_one::<Provider>("source").expect("has default");
+        provider.name().to_owned()
+    } else {
+        let input = matches
+            .get_one::<PathBuf>("input")
+            .expect("required by group");
+        input.display().to_string()
+    };
+    let meta = meta::Meta {
+        version: crate_version!(),
+        currency: currency.to_owned(),
+        days: chart.days.map(|days| days.to_string()),
+        source,
+        coins,
+        ratio: matches.get_flag("ratio"),
+        axes: (&chart.ranges).into(),
+    };
+    create_parent_dir(path)?;
+    meta::write(path, &meta)
+}
+
+/// When the response for the coin `id` in `currency` was fetched, as the
+/// cache remembers it, or now if it isn't cached.
+fn fetched_at(
+    matches: &ArgMatches,
+    id: &str,
+    currency: &str,
+    days: Days,
+) -> Result<DateTime<Utc>, Error> {
+    let key = data_source(matches)?.plan(id, currency, days).key;
+    let stored = cache(matches, Duration::MAX)?.and_then(|cache| cache.stored_at(&key));
+    Ok(stored.map_or_else(Utc::now, DateTime::from))
+}
+
+/// The file `output` is written to as `format`, none for stdout and the
+/// terminal.
+fn written_path(output: Option<&PathBuf>, format: Format) -> Option<PathBuf> {
+    let extension = match format {
+        Format::Svg => "svg",
+        Format::Png => "png",
+        Format::Html => "html",
+        Format::Terminal => return None,
+    };
+    match output {
+        Some(path) if path.as_os_str() == "-" => None,
+        Some(path) => Some(path.clone()),
+        None => Some(default_output(extension)),
+    }
+}
+
+/// Draws `chart` to every `--output`, unless all files were drawn from what
+/// has the `fingerprint` already. Their fingerprint is kept next to them.
+fn render(
+    matches: &ArgMatches,
+    config: &Config,
+    chart: &Chart,
+    fingerprint: Option<&str>,
+) -> Result<(), Error> {
+    if chart.panels() == 0 {
+        return Err(Error::EmptyData(
+            "none of the panels requested with --metric has data to plot".to_owned(),
+        ));
+    }
+    for what in chart.linear_panels() {
+        log::warning!("{what} data contains non-positive values, ignoring --log-scale");
+    }
+    let outputs: Vec<Option<&PathBuf>> = match matches.get_many::<PathBuf>("output") {
+        Some(outputs) => outputs.map(Some).collect(),
+        None => vec![config.output.as_ref()],
+    };
+    let formats: Vec<Format> = matches
+        .get_many::<Format>("format")
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+    let destinations: Vec<_> = outputs
+        .into_iter()
+        .enumerate()
+        .map(|(i, output)| {
+            let format = formats
+                .get(if formats.len() == 1 { 0 } else { i })
+                .copied()
+                .or_else(|| output.and_then(|path| Format::from_path(path)))
+                .unwrap_or(Format::Svg);
+            (output, format)
+        })
+        .collect();
+
+    if let Some(fingerprint) = fingerprint {
+        // stdout and the terminal have to be drawn to anyway
+        let unchanged = destinations.iter().all(|&(output, format)| {
+            written_path(output, format)
+                .is_some_and(|path| fingerprint::unchanged(&path, fingerprint))
+        });
+        if unchanged {
+            log::info!("unchanged since last drawn, skipping");
+            return Ok(());
+        }
+    }
+
+    // the data is collected once, only the drawing is repeated
+    for (output, format) in destinations {
+        if let Some(written) = render_to(matches, chart, output, format)? {
+            if let Some(fingerprint) = fingerprint {
+                fingerprint::save(&written, fingerprint)?;
+            }
+            log::info!("wrote {}", written.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws `chart` as `format` to `output`, returning the file written, if
+/// any.
+fn render_to(
+    matches: &ArgMatches,
+    chart: &Chart,
+    output: Option<&PathBuf>,
+    format: Format,
+) -> Result<Option<PathBuf>, Error> {
+    let to_stdout = output.is_some_and(|path| path.as_os_str() == "-");
+    let minify = matches.get_flag("minify");
+    match format {
+        Format::Svg if to_stdout => {
+            io::stdout()
+                .lock()
+                .write_all(svg(chart, minify)?.as_bytes())?;
+        }
+        Format::Svg => {
+            let output = output_path(output, "svg")?;
+            write_file(&output, &svg(chart, minify)?)?;
+            return Ok(Some(output));
+        }
+        Format::Html => {
+            let output = if to_stdout {
+                None
+            } else {
+                Some(output_path(output, "html")?)
+            };
+            write_html(chart, minify, output.as_deref())?;
+            return Ok(output);
+        }
+        Format::Png if to_stdout => {
+            return Err(Error::InvalidArgs(
+                "only SVG and HTML can be written to stdout".to_owned(),
+            ));
+        }
+        Format::Png => {
+            let output = output_path(output, "png")?;
+            let root = BitMapBackend::new(&output, chart.options.size).into_drawing_area();
+            // unlike SVG, a bitmap has no transparent default background
+            root.fill(&chart.options.palette.background)?;
+            plot(&root, chart)?;
+            root.present()?;
+            drop(root);
+            return Ok(Some(output));
+        }
+        Format::Terminal => {
+            // the configured size is in pixels, so only the command line applies
+            let (columns, rows) = terminal::size();
+            let size = (
+                matches.get_one::<u32>("width").copied().unwrap_or(columns),
+                matches.get_one::<u32>("height").copied().unwrap_or(rows),
+            );
+            terminal::plot(chart, size)?;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Collects what to draw besides the series, computed from the prices of
+/// `coin` with the id `id`. Lines across the chart span `x_range`.
+fn decorations(
+    matches: &ArgMatches,
+    id: &str,
+    coin: &Coin,
+    x_range: &Range<DateTime<Utc>>,
+    palette: &Palette,
+) -> Decorations {
+    let timezone = timezone(matches);
+    let mut decorations = Decorations {
+        annotation: annotation(matches, id, timezone),
+        markers: Vec::new(),
+        spans: Vec::new(),
+        weekends: if matches.get_flag("shade-weekends") {
+            weekends(x_range, timezone)
+        } else {
+            Vec::new()
+        },
+        vlines: matches
+            .get_many::<DateTime<Utc>>("vline")
+            .unwrap_or_default()
+            .map(|&when| Annotation {
+                when,
+                label: when
+                    .with_timezone(&timezone)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+            })
+            .collect(),
+        price_overlays: Vec::new(),
+        price_band: None,
+        crossovers: Vec::new(),
+        change: matches
+            .get_flag("show-change")
+            .then(|| change(coin, palette))
+            .flatten(),
+        rsi: None,
+        returns: None,
+    };
+    if let Some(&days) = matches.get_one::<usize>("sma") {
+        decorations.price_overlays.push(Overlay {
+            points: simple_moving_average(coin.data.iter_prices(), days),
+            color: palette.overlay,
+            label: format!("{days}-day SMA"),
+            dashed: false,
+        });
+    }
+    if let Some(&days) = matches.get_one::<usize>("ema") {
+        decorations.price_overlays.push(Overlay {
+            points: exponential_moving_average(coin.data.iter_prices(), days),
+            color: palette.ema,
+            label: format!("{days}-day EMA"),
+            dashed: false,
+        });
+    }
+    if let Some(windows) = crossover_windows(matches) {
+        let (averages, crossovers) = crossover(coin, windows, palette);
+        decorations.price_overlays.extend(averages);
+        decorations.crossovers = crossovers;
+    }
+    if let Some(&days) = matches.get_one::<usize>("bollinger") {
+        let (mean, band) = bollinger_bands(coin.data.iter_prices(), days);
+        decorations.price_overlays.push(Overlay {
+            points: mean,
+            color: palette.band,
+            label: format!("{days}-day mean"),
+            dashed: true,
+        });
+        decorations.price_band = Some(Band {
+            points: band,
+            color: palette.band,
+            label: "Bollinger Bands (2σ)".to_owned(),
+        });
+    }
+    if matches.get_flag("trendline") {
+        decorations
+            .price_overlays
+            .extend(trendline(coin, x_range, palette));
+    }
+    if matches.get_flag("mark-extremes") {
+        decorations.markers = extremes(coin, palette, timezone);
+    }
+    if matches.get_flag("drawdown") {
+        decorations.spans.extend(drawdown(coin, palette));
+    }
+    if let Some(&days) = matches.get_one::<usize>("rsi") {
+        decorations.rsi = Some(Overlay {
+            points: relative_strength_index(coin.data.iter_prices(), days),
+            color: palette.lines[0],
+            label: format!("{days}-day RSI"),
+            dashed: false,
+        });
+    }
+    if matches.get_flag("returns-hist") {
+        let bins = *matches.get_one::<usize>("bins").expect("has default");
+        decorations.returns =
+            Histogram::of(&eth_hist_plot::daily_returns(coin.data.iter_prices()), bins);
+        if decorations.returns.is_none() {
+            log::warning!(
+                "too few {} prices for a histogram of the returns",
+                coin.name
+            );
+        }
+    }
+    decorations
+}
+
+/// Fits a line through the prices of `coin` across `x_range` and prints its
+/// slope.
+fn trendline(coin: &Coin, x_range: &Range<DateTime<Utc>>, palette: &Palette) -> Option<Overlay> {
+    let Some((slope, intercept)) = eth_hist_plot::linear_regression(coin.data.iter_prices()) else {
+        log::warning!("too few {} prices for a trendline", coin.name);
+        return None;
+    };
+
+    let at = |when: DateTime<Utc>| {
+        // exact enough for a line across the chart
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = when.timestamp() as f64;
+        (when, intercept + slope * seconds)
+    };
+    log::info!("{} trend: {:+.2} per day", coin.name, slope * 86_400.0);
+    Some(Overlay {
+        points: vec![at(x_range.start), at(x_range.end)],
+        color: palette.foreground,
+        label: "trendline".to_owned(),
+        dashed: false,
+    })
+}
+
+/// Finds the largest drawdown of the prices of `coin` and prints it.
+fn drawdown(coin: &Coin, palette: &Palette) -> Option<Span> {
+    let Some(drawdown) = Drawdown::of(coin.data.iter_prices()) else {
+        log::info!("{}: no drawdown", coin.name);
+        return None;
+    };
+
+    let date = |timestamp: DateTime<Utc>| timestamp.format("%Y-%m-%d");
+    let label = format!("max drawdown -{:.2}%", drawdown.percent);
+    log::info!(
+        "{}: {label} from {:.2} on {} to {:.2} on {}",
+        coin.name,
+        drawdown.peak.1,
+        date(drawdown.peak.0),
+        drawdown.trough.1,
+        date(drawdown.trough.0),
+    );
+    Some(Span {
+        range: drawdown.peak.0..drawdown.trough.0,
+        color: palette.low,
+        label,
+    })
+}
+
+/// Markers of the highest and the lowest price of `coin`, if it has any,
+/// labeled with the day in `timezone`.
+fn extremes(coin: &Coin, palette: &Palette, timezone: Tz) -> Vec<Marker> {
+    let Some(stats) = Stats::of(coin.data.iter_prices()) else {
+        return Vec::new();
+    };
+    let marker = |(when, value): (DateTime<Utc>, f64), what: &str, color| Marker {
+        point: (when, value),
+        color,
+        label: format!(
+            "{what} {value:.2} on {}",
+            when.with_timezone(&timezone).format("%Y-%m-%d")
+        ),
+    };
+    vec![
+        marker(stats.max, "high", palette.high),
+        marker(stats.min, "low", palette.low),
+    ]
+}
+
+/// The change of the prices of `coin` from the first to the last one there
+/// is, e.g. `+42.3% over 365d`, colored like rising or falling candles.
+fn change(coin: &Coin, palette: &Palette) -> Option<Change> {
+    let mut prices = coin
+        .data
+        .iter_prices()
+        .filter(|datum| datum.price().is_some());
+    let first = *prices.next()?.timestamp();
+    let last = prices.next_back().map_or(first, |datum| *datum.timestamp());
+    let stats = Stats::of(coin.data.iter_prices())?;
+
+    let span = last - first;
+    let span = if span < chrono::Duration::days(1) {
+        format!("{}h", span.num_hours())
+    } else {
+        format!("{}d", span.num_days())
+    };
+    Some(Change {
+        label: format!("{:+.1}% over {span}", stats.change),
+        color: if stats.change < 0.0 {
+            palette.down
+        } else {
+            palette.up
+        },
+    })
+}
+
+/// Restricts the data of all `coins` to the interval from `from` to `to`,
+/// failing if that leaves nothing to plot.
+fn retain_between(
+    coins: &mut [Coin],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<(), Error> {
+    for coin in coins {
+        coin.data.retain_between(from, to);
+        for (what, len) in [
+            ("price", coin.data.iter_prices().len()),
+            ("market cap", coin.data.iter_market_caps().len()),
+            ("volume", coin.data.iter_total_volumes().len()),
+        ] {
+            if len == 0 {
+                let bound = |bound: Option<DateTime<Utc>>| {
+                    bound.map_or_else(|| "…".to_owned(), |bound| bound.to_rfc3339())
+                };
+                return Err(Error::EmptyData(format!(
+                    "no {} {what} data between {} and {}",
+                    coin.name,
+                    bound(from),
+                    bound(to)
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The simple moving averages of `coin` over `fast` and `slow` days and
+/// where they cross, which is printed.
+fn crossover(
+    coin: &Coin,
+    (fast, slow): (usize, usize),
+    palette: &Palette,
+) -> (Vec<Overlay>, Vec<Crossover>) {
+    let averages = [fast, slow].map(|days| simple_moving_average(coin.data.iter_prices(), days));
+    let crossovers = crossovers(&averages[0], &averages[1]);
+    for crossover in &crossovers {
+        log::info!(
+            "{}: {} on {}",
+            coin.name,
+            crossover.kind.name(),
+            crossover.point.0.format("%Y-%m-%d")
+        );
+    }
+
+    let overlays = averages
+        .into_iter()
+        .zip([fast, slow])
+        .zip([palette.overlay, palette.ema])
+        .map(|((points, days), color)| Overlay {
+            points,
+            color,
+            label: format!("{days}-day SMA"),
+            dashed: false,
+        })
+        .collect();
+    (overlays, crossovers)
+}
+
+/// The FAST and SLOW days of `--crossover`, if given.
+fn crossover_windows(matches: &ArgMatches) -> Option<(usize, usize)> {
+    let mut windows = matches.get_many::<usize>("crossover")?.copied();
+    Some((windows.next()?, windows.next()?))
+}
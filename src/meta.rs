@@ -0,0 +1,96 @@
+//! A description of how a chart came about, written next to it to tell
+//! charts of different runs apart.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use eth_hist_plot::chart::{Bounds, Ranges};
+use eth_hist_plot::Error;
+use serde::Serialize;
+
+/// What the chart was drawn from and how its axes are scaled.
+#[derive(Serialize)]
+pub struct Meta {
+    /// Of `eth_hist_plot` drawing the chart.
+    pub version: &'static str,
+    pub currency: String,
+    /// As fetched, none for a saved response.
+    pub days: Option<String>,
+    /// The name of the API fetched from, or the file read.
+    pub source: String,
+    pub coins: Vec<CoinMeta>,
+    /// Whether the chart is of the prices of the first coin in the second,
+    /// rather than of the coins themselves.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ratio: bool,
+    pub axes: Axes,
+}
+
+#[derive(Serialize)]
+pub struct CoinMeta {
+    pub id: String,
+    /// When the response was fetched, as the cache has it if it came from
+    /// there, none for a saved response.
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Of each series, after cleaning and restricting to the window.
+    pub prices: usize,
+    pub market_caps: usize,
+    pub total_volumes: usize,
+}
+
+/// The axes of each panel, the market cap and the volume if drawn.
+#[derive(Serialize)]
+pub struct Axes {
+    pub price: Panel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap: Option<Panel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<Panel>,
+}
+
+/// The least and the greatest value on the axes of a panel.
+#[derive(Serialize)]
+pub struct Panel {
+    pub x: Extent<DateTime<Utc>>,
+    pub y: Extent<f64>,
+}
+
+#[derive(Serialize)]
+pub struct Extent<T> {
+    pub min: T,
+    pub max: T,
+}
+
+impl From<&Ranges> for Axes {
+    fn from(ranges: &Ranges) -> Self {
+        let panel = |(x, y): &Bounds| Panel {
+            x: Extent {
+                min: x.start,
+                max: x.end,
+            },
+            y: Extent {
+                min: y.start,
+                max: y.end,
+            },
+        };
+        Self {
+            price: panel(&ranges.price),
+            market_cap: ranges.market_cap.as_ref().map(panel),
+            volume: ranges.volume.as_ref().map(panel),
+        }
+    }
+}
+
+/// Writes `meta` as pretty JSON to `path`.
+pub fn write(path: &Path, meta: &Meta) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(meta)?;
+    fs::write(path, json + "\n").map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("cannot write {}: {err}", path.display()),
+        )
+    })?;
+    Ok(())
+}
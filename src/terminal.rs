@@ -0,0 +1,98 @@
+//! Rendering the price panel as braille characters for terminals.
+
+use std::io::{self, IsTerminal, Write};
+
+use chrono::{DateTime, Duration, Utc};
+use eth_hist_plot::chart::Chart;
+use eth_hist_plot::Error;
+use terminal_size::{terminal_size, Height, Width};
+use textplots::{ColorPlot, LabelBuilder, LabelFormat, Shape};
+
+/// Used if stdout is not connected to a terminal.
+const DEFAULT_SIZE: (u32, u32) = (80, 24);
+
+/// Character cells kept free to the right of the plot for the y labels.
+const LABEL_WIDTH: u32 = 12;
+
+/// Returns the size of the terminal in character cells.
+pub fn size() -> (u32, u32) {
+    terminal_size().map_or(DEFAULT_SIZE, |(Width(width), Height(height))| {
+        (width.into(), height.into())
+    })
+}
+
+/// Draws the prices of all coins in `chart` to stdout, using `size`
+/// character cells including caption and labels.
+// textplots works with f32, which is plenty for a few hundred dots
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+pub fn plot(chart: &Chart, size: (u32, u32)) -> Result<(), Error> {
+    let (x_range, y_range) = &chart.ranges.price;
+    let start = x_range.start;
+    let timezone = chart.options.grid.timezone;
+    let days = |when: DateTime<Utc>| (when - start).num_seconds() as f32 / 86_400.0;
+
+    let points: Vec<Vec<(f32, f32)>> = chart
+        .coins
+        .iter()
+        .map(|coin| {
+            coin.data
+                .iter_prices()
+                .filter_map(|datum| {
+                    datum
+                        .price()
+                        .map(|price| (days(*datum.timestamp()), price as f32))
+                })
+                .collect()
+        })
+        .collect();
+    let shapes: Vec<Shape<'_>> = points.iter().map(|points| Shape::Lines(points)).collect();
+
+    // a braille character is 2 dots wide and 4 dots high, textplots needs
+    // at least 32 x 3 dots
+    let (columns, rows) = size;
+    let width = (columns.saturating_sub(LABEL_WIDTH) * 2).max(32);
+    // caption, x labels and the shell prompt
+    let height = (rows.saturating_sub(3) * 4).max(3);
+
+    let mut canvas = textplots::Chart::new_with_y_range(
+        width,
+        height,
+        0.0,
+        days(x_range.end),
+        y_range.start as f32,
+        y_range.end as f32,
+    );
+    let mut canvas = canvas
+        .x_label_format(LabelFormat::Custom(Box::new(move |days| {
+            (start + Duration::seconds((f64::from(days) * 86_400.0) as i64))
+                .with_timezone(&timezone)
+                .format("%Y-%m-%d")
+                .to_string()
+        })))
+        .y_label_format(LabelFormat::Custom(Box::new(|price| format!("{price:.2}"))));
+    // colors are escape sequences, which would end up in redirected output
+    let colored = io::stdout().is_terminal();
+    for (shape, color) in shapes
+        .iter()
+        .zip(chart.options.palette.lines.iter().cycle())
+    {
+        canvas = if colored {
+            canvas.linecolorplot(shape, rgb::RGB8::new(color.0, color.1, color.2))
+        } else {
+            textplots::Plot::lineplot(canvas, shape)
+        };
+    }
+    canvas.axis();
+    canvas.figures();
+
+    let unit = if chart.options.normalized {
+        "indexed to 100"
+    } else {
+        &chart.options.currency
+    };
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{} price {unit}", chart.name())?;
+    write!(stdout, "{canvas}")?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Colors of the charts.
+
+use plotters::style::{RGBColor, BLACK, BLUE, GREEN, RED, WHITE};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    #[must_use]
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Self::Light => &LIGHT,
+            Self::Dark => &DARK,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub background: RGBColor,
+    /// Axes, mesh, text and borders.
+    pub foreground: RGBColor,
+    /// Colors of the lines of the coins, in order.
+    pub lines: [RGBColor; 5],
+    /// Color of the simple moving average.
+    pub overlay: RGBColor,
+    /// Color of the exponential moving average.
+    pub ema: RGBColor,
+    /// Color of the annotation marker.
+    pub annotation: RGBColor,
+    /// Colors of the markers of the highest and the lowest price.
+    pub high: RGBColor,
+    pub low: RGBColor,
+    /// Color of the Bollinger Bands and their mean.
+    pub band: RGBColor,
+    /// Colors of the candles closing higher and lower than they opened.
+    pub up: RGBColor,
+    pub down: RGBColor,
+}
+
+/// Parses a color written as `#rrggbb` or `#rgb` in hexadecimal, as in CSS.
+///
+/// # Errors
+///
+/// Describes for the command line what `s` is instead.
+pub fn parse_color(s: &str) -> Result<RGBColor, String> {
+    let invalid = || format!("`{s}` is not a color, expected a hex code like `#ff8800`");
+    let digits = s.strip_prefix('#').ok_or_else(invalid)?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+    match digits.len() {
+        6 => Ok(RGBColor(
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        )),
+        // each digit doubled, `#f80` is `#ff8800`
+        3 => Ok(RGBColor(
+            channel(&digits[0..1].repeat(2))?,
+            channel(&digits[1..2].repeat(2))?,
+            channel(&digits[2..3].repeat(2))?,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
+const LIGHT: Palette = Palette {
+    background: WHITE,
+    foreground: BLACK,
+    lines: [
+        RED,
+        RGBColor(255, 140, 0),
+        RGBColor(128, 0, 128),
+        RGBColor(0, 128, 128),
+        BLACK,
+    ],
+    overlay: GREEN,
+    ema: RGBColor(0, 160, 255),
+    annotation: BLUE,
+    high: RGBColor(0, 160, 0),
+    low: RGBColor(200, 0, 200),
+    band: RGBColor(128, 128, 128),
+    up: RGBColor(0, 150, 0),
+    down: RGBColor(210, 0, 0),
+};
+
+const DARK: Palette = Palette {
+    background: RGBColor(30, 30, 30),
+    foreground: RGBColor(220, 220, 220),
+    lines: [
+        RGBColor(255, 99, 71),
+        RGBColor(255, 190, 60),
+        RGBColor(200, 130, 255),
+        RGBColor(64, 224, 208),
+        RGBColor(240, 240, 240),
+    ],
+    overlay: RGBColor(120, 230, 120),
+    ema: RGBColor(255, 150, 200),
+    annotation: RGBColor(110, 170, 255),
+    high: RGBColor(100, 220, 100),
+    low: RGBColor(255, 100, 255),
+    band: RGBColor(170, 170, 170),
+    up: RGBColor(80, 200, 120),
+    down: RGBColor(255, 90, 90),
+};
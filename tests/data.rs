@@ -0,0 +1,218 @@
+//! What is computed from the series before plotting.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use eth_hist_plot::chart::{Coin, Metric, Ranges};
+use eth_hist_plot::{Cross, Crossover, Data, Datum, Stats};
+use serde_json::json;
+
+/// Values a day apart from 2024-01-01 on, the same for all series.
+fn data(values: &[Option<f64>]) -> Data {
+    let days: Vec<_> = values.iter().copied().enumerate().collect();
+    data_on(&days)
+}
+
+/// Values on the given days after 2024-01-01, in the given order, the same
+/// for all series.
+fn data_on(values: &[(usize, Option<f64>)]) -> Data {
+    let series: Vec<_> = values
+        .iter()
+        .map(|&(day, value)| json!([day_of(day).timestamp_millis(), value]))
+        .collect();
+    let response = json!({
+        "prices": series,
+        "market_caps": series,
+        "total_volumes": series,
+    });
+    eth_hist_plot::parse(response.to_string().as_bytes()).unwrap()
+}
+
+fn day_of(day: usize) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day.try_into().unwrap())
+}
+
+fn prices(data: &Data) -> Vec<Option<f64>> {
+    data.iter_prices().map(Datum::price).collect()
+}
+
+#[test]
+fn fills_short_gaps() {
+    let mut data = data(&[Some(1.0), None, Some(3.0), None, None, Some(6.0)]);
+    data.fill_gaps(1);
+    assert_eq!(
+        prices(&data),
+        [Some(1.0), Some(2.0), Some(3.0), None, None, Some(6.0)]
+    );
+    assert_eq!(
+        data.iter_total_volumes().nth(1).and_then(Datum::price),
+        Some(2.0)
+    );
+}
+
+#[test]
+fn averages_full_windows() {
+    let data = data(&[Some(1.0), Some(2.0), None, Some(3.0), Some(7.0)]);
+    let average = eth_hist_plot::simple_moving_average(data.iter_prices(), 2);
+    assert_eq!(
+        average,
+        [(day_of(1), 1.5), (day_of(3), 2.5), (day_of(4), 5.0)]
+    );
+}
+
+#[test]
+fn averages_nothing_over_too_few_prices() {
+    let data = data(&[Some(1.0), Some(2.0)]);
+    assert!(eth_hist_plot::simple_moving_average(data.iter_prices(), 3).is_empty());
+    assert_eq!(
+        eth_hist_plot::simple_moving_average(data.iter_prices(), 2),
+        [(day_of(1), 1.5)]
+    );
+}
+
+#[test]
+fn bands_two_standard_deviations_around_the_mean() {
+    let data = data(&[Some(1.0), Some(3.0), Some(5.0)]);
+    let (mean, band) = eth_hist_plot::bollinger_bands(data.iter_prices(), 2);
+    assert_eq!(mean, [(day_of(1), 2.0), (day_of(2), 4.0)]);
+    assert_eq!(band, [(day_of(1), 0.0, 4.0), (day_of(2), 2.0, 6.0)]);
+}
+
+#[test]
+fn keeps_the_rsi_within_bounds() {
+    let values: Vec<_> = (0..30_u32)
+        .map(|day| Some(f64::from(day * 7 % 11)))
+        .collect();
+    let rsi = eth_hist_plot::relative_strength_index(data(&values).iter_prices(), 14);
+    assert_eq!(rsi.len(), 16);
+    assert!(rsi.iter().all(|&(_, rsi)| (0.0..=100.0).contains(&rsi)));
+
+    let rising: Vec<_> = (0..20_u32).map(|day| Some(f64::from(day))).collect();
+    let rsi = eth_hist_plot::relative_strength_index(data(&rising).iter_prices(), 14);
+    assert!(rsi.iter().all(|&(_, rsi)| rsi == 100.0));
+    let falling: Vec<_> = rising.into_iter().rev().collect();
+    let rsi = eth_hist_plot::relative_strength_index(data(&falling).iter_prices(), 14);
+    assert!(rsi.iter().all(|&(_, rsi)| rsi == 0.0));
+}
+
+#[test]
+fn downsamples_to_the_threshold() {
+    let points: Vec<_> = (0..10_000)
+        .map(|day| (day_of(day), f64::from(u32::try_from(day % 97).unwrap())))
+        .collect();
+    let downsampled = eth_hist_plot::downsample(&points, 500);
+    assert_eq!(downsampled.len(), 500);
+    assert_eq!(downsampled.first(), points.first());
+    assert_eq!(downsampled.last(), points.last());
+}
+
+#[test]
+fn sorts_by_time() {
+    let data = data_on(&[
+        (2, Some(2.0)),
+        (0, Some(0.0)),
+        (3, Some(3.0)),
+        (1, Some(1.0)),
+    ]);
+    assert_eq!(prices(&data), [Some(0.0), Some(1.0), Some(2.0), Some(3.0)]);
+}
+
+#[test]
+fn keeps_the_last_of_repeated_timestamps() {
+    let data = data_on(&[(0, Some(1.0)), (1, Some(2.0)), (0, Some(3.0))]);
+    let timestamps: Vec<_> = data.iter_prices().map(|datum| *datum.timestamp()).collect();
+    assert_eq!(timestamps, [day_of(0), day_of(1)]);
+    assert_eq!(prices(&data), [Some(3.0), Some(2.0)]);
+}
+
+#[test]
+fn drops_non_finite_values() {
+    // indexing to a price of zero makes it NaN and all others infinite
+    let mut data = data(&[Some(0.0), Some(2.0), None, Some(4.0)]);
+    data.index_prices();
+    assert_eq!(data.drop_non_finite(), 3);
+    assert_eq!(prices(&data), [None, None, None, None]);
+    assert!(Stats::of(data.iter_prices()).is_none());
+
+    let market_caps = Stats::of(data.iter_market_caps()).unwrap();
+    assert_eq!((market_caps.min.1, market_caps.max.1), (0.0, 4.0));
+}
+
+#[test]
+fn seeds_the_exponential_average_with_the_first_price() {
+    let data = data(&[Some(2.0), Some(4.0), Some(8.0)]);
+    // three days of prices make a smoothing factor of one half
+    let average = eth_hist_plot::exponential_moving_average(data.iter_prices(), 3);
+    assert_eq!(
+        average,
+        [(day_of(0), 2.0), (day_of(1), 3.0), (day_of(2), 5.5)]
+    );
+}
+
+#[test]
+fn marks_only_real_crossings() {
+    let data = data(&[
+        Some(5.0),
+        Some(4.0),
+        Some(3.0),
+        Some(6.0),
+        Some(6.0),
+        Some(1.0),
+    ]);
+    let fast = eth_hist_plot::simple_moving_average(data.iter_prices(), 1);
+    let slow = eth_hist_plot::simple_moving_average(data.iter_prices(), 3);
+    // the slow average is 4, 13/3, 5 and 13/3 from the third day on
+    assert_eq!(
+        eth_hist_plot::crossovers(&fast, &slow),
+        [
+            Crossover {
+                point: (day_of(3), 13.0 / 3.0),
+                kind: Cross::Golden
+            },
+            Crossover {
+                point: (day_of(5), 13.0 / 3.0),
+                kind: Cross::Death
+            },
+        ]
+    );
+}
+
+#[test]
+fn cuts_weekends_to_the_range() {
+    // 2024-01-01 is a Monday
+    assert_eq!(
+        eth_hist_plot::weekends(&(day_of(1)..day_of(13)), Tz::UTC),
+        [day_of(5)..day_of(7), day_of(12)..day_of(13)]
+    );
+    assert_eq!(
+        eth_hist_plot::weekends(&(day_of(6)..day_of(9)), Tz::UTC),
+        [day_of(6)..day_of(7)]
+    );
+    assert!(eth_hist_plot::weekends(&(day_of(5)..day_of(6)), Tz::UTC).is_empty());
+
+    // Tokyo is 9 hours ahead
+    let hours = |day, hours| day_of(day) + Duration::hours(hours);
+    assert_eq!(
+        eth_hist_plot::weekends(&(day_of(1)..day_of(8)), Tz::Asia__Tokyo),
+        [hours(4, 15)..hours(6, 15)]
+    );
+}
+
+#[test]
+fn leaves_non_finite_values_out_of_the_range() {
+    // JSON has no NaN, so the data is made up of data points directly
+    let series: Vec<_> = [Some(1.0), Some(f64::NAN), Some(3.0)]
+        .into_iter()
+        .enumerate()
+        .map(|(day, value)| Datum::new(day_of(day), value))
+        .collect();
+    let mut data = Data::new(series.clone(), series.clone(), series);
+    assert_eq!(data.drop_non_finite(), 3);
+    assert_eq!(prices(&data), [Some(1.0), None, Some(3.0)]);
+
+    let coins = [Coin {
+        name: "Ethereum".to_owned(),
+        data,
+    }];
+    let ranges = Ranges::new(&coins, None, &[Metric::Price]).unwrap();
+    assert_eq!(ranges.price.1, 1.0..3.0);
+}
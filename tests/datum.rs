@@ -0,0 +1,53 @@
+//! How values of the responses deserialize.
+
+use chrono::{TimeZone, Utc};
+use eth_hist_plot::Datum;
+
+fn datum(json: &str) -> serde_json::Result<Datum> {
+    serde_json::from_str(json)
+}
+
+#[test]
+fn decodes_the_timestamp_in_milliseconds() {
+    let datum = datum("[1668851750741, 1234.5]").unwrap();
+    let expected = Utc.with_ymd_and_hms(2022, 11, 19, 9, 55, 50).unwrap()
+        + chrono::Duration::milliseconds(741);
+    assert_eq!(*datum.timestamp(), expected);
+    assert_eq!(datum.price(), Some(1234.5));
+}
+
+#[test]
+fn decodes_integer_values() {
+    assert_eq!(
+        datum("[1668851750741, 1234]").unwrap().price(),
+        Some(1234.0)
+    );
+}
+
+#[test]
+fn decodes_null_as_missing() {
+    let datum = datum("[1668851750741, null]").unwrap();
+    assert_eq!(datum.price(), None);
+}
+
+#[test]
+fn rejects_malformed_entries() {
+    for json in [
+        "[1668851750741]",
+        "[1668851750741, 1234.5, 1]",
+        "[\"1668851750741\", 1234.5]",
+        "[1668851750741, \"1234.5\"]",
+        "{\"timestamp\": 1668851750741, \"price\": 1234.5}",
+    ] {
+        assert!(datum(json).is_err(), "accepted {json}");
+    }
+}
+
+#[test]
+fn serializes_back_to_the_same_pair() {
+    let datum = datum("[1668851750741, null]").unwrap();
+    assert_eq!(
+        serde_json::to_string(&datum).unwrap(),
+        "[1668851750741,null]"
+    );
+}
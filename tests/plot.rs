@@ -0,0 +1,500 @@
+//! Runs the whole pipeline on a saved response, from parsing it to the SVG.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use eth_hist_plot::chart::Metric;
+use eth_hist_plot::theme::Theme;
+use eth_hist_plot::{Data, Error, PlotOptions};
+
+/// A `market_chart` response of 12 days of Ethereum prices.
+const RESPONSE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/market_chart.json"
+);
+/// [`RESPONSE`], gzipped.
+const GZIPPED: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/market_chart.json.gz"
+);
+/// 2024-01-01, the first day of [`RESPONSE`], in milliseconds since the
+/// epoch.
+const START: u64 = 1_704_067_200_000;
+const HOUR: u64 = 3_600_000;
+const DAY: u64 = 86_400_000;
+const CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/config.toml");
+
+/// Saves `response` as `name` with the temporary files of the tests.
+fn save(name: &str, response: impl AsRef<[u8]>) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, response).unwrap();
+    path
+}
+
+/// Saves a response named `name` of `points`, in milliseconds since the
+/// epoch, as every series.
+fn synthetic(name: &str, points: impl IntoIterator<Item = (u64, f64)>) -> PathBuf {
+    let series: Vec<_> = points
+        .into_iter()
+        .map(|(timestamp, value)| format!("[{timestamp}, {value}]"))
+        .collect();
+    let series = series.join(",");
+    save(
+        name,
+        format!(
+            r#"{{"prices": [{series}], "market_caps": [{series}], "total_volumes": [{series}]}}"#
+        ),
+    )
+}
+
+/// Plots [`RESPONSE`] with `args` to stdout.
+fn run(args: &[&str]) -> Output {
+    run_on(Path::new(RESPONSE), args)
+}
+
+/// Plots the response saved as `input` with `args` to stdout.
+fn run_on(input: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_eth_hist_plot"))
+        .args(["--config", CONFIG, "--input", input.to_str().unwrap()])
+        .args(["--output", "-"])
+        .args(args)
+        .output()
+        .expect("runs")
+}
+
+/// Plots [`RESPONSE`] with `args`, returning the SVG.
+fn plot(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("SVG is UTF-8")
+}
+
+#[test]
+fn parses_all_series() {
+    let data = eth_hist_plot::parse(File::open(RESPONSE).unwrap()).unwrap();
+    assert_eq!(data.iter_prices().len(), 12);
+    assert_eq!(data.iter_market_caps().len(), 12);
+    assert_eq!(data.iter_total_volumes().len(), 12);
+    assert_eq!(data.iter_prices().next().unwrap().price(), Some(2281.47));
+    data.validate(chrono::Duration::zero()).unwrap();
+}
+
+#[test]
+fn renders_svg_in_memory() {
+    let data = eth_hist_plot::parse(File::open(RESPONSE).unwrap()).unwrap();
+    let options = PlotOptions {
+        name: "Ether".to_owned(),
+        ..PlotOptions::default()
+    };
+    let svg = eth_hist_plot::render_svg(&data, &options).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("Ether price"));
+}
+
+#[test]
+fn refuses_to_render_no_data() {
+    let data = Data::new(vec![], vec![], vec![]);
+    let result = eth_hist_plot::render_svg(&data, &PlotOptions::default());
+    assert!(matches!(result, Err(Error::EmptyData(_))));
+
+    // prices, but no panel to plot them on
+    let data = eth_hist_plot::parse(File::open(RESPONSE).unwrap()).unwrap();
+    let options = PlotOptions {
+        metrics: Vec::new(),
+        ..PlotOptions::default()
+    };
+    let result = eth_hist_plot::render_svg(&data, &options);
+    assert!(matches!(result, Err(Error::EmptyData(_))));
+}
+
+#[test]
+fn plots_all_panels() {
+    let svg = plot(&[]);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    for caption in ["Ethereum price", "Ethereum market cap", "Ethereum volume"] {
+        assert!(svg.contains(caption), "no caption {caption}");
+    }
+    assert!(svg.contains("<polyline"));
+}
+
+#[test]
+fn plots_only_the_metrics_asked_for() {
+    let svg = plot(&["--metric", "price"]);
+    assert!(svg.contains("Ethereum price"));
+    assert!(!svg.contains("Ethereum market cap"));
+    assert!(!svg.contains("Ethereum volume"));
+}
+
+#[test]
+fn draws_the_title() {
+    let svg = plot(&["--title", "Twelve days of Ether"]);
+    assert!(svg.contains("Twelve days of Ether"));
+}
+
+#[test]
+fn fails_cleanly_without_prices() {
+    for (name, prices) in [
+        ("empty.json", ""),
+        ("missing.json", "[1704067200000, null]"),
+    ] {
+        let response = format!(
+            r#"{{"prices": [{prices}], "market_caps": [{prices}], "total_volumes": [{prices}]}}"#
+        );
+        let output = run_on(&save(name, response), &[]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("no price data available for the requested range"),
+            "{stderr}"
+        );
+        assert!(!stderr.contains("panicked"), "{stderr}");
+    }
+}
+
+#[test]
+fn averages_over_days_of_prices() {
+    assert!(plot(&["--sma", "3"]).contains("3-day SMA"));
+    // more days than there are prices, no line rather than a panic
+    assert!(!plot(&["--sma", "20"]).contains("SMA"));
+
+    // 4 days of hourly prices, 24 a day: enough for 2 days, not for 5
+    let input = synthetic(
+        "sma-hourly.json",
+        (0..96_u32).map(|i| (START + u64::from(i) * HOUR, f64::from(1000 + i % 7))),
+    );
+    for (days, drawn) in [("2", true), ("5", false)] {
+        let output = run_on(&input, &["--sma", days]);
+        assert!(output.status.success());
+        let svg = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(svg.contains("-day SMA"), drawn, "--sma {days}");
+    }
+}
+
+#[test]
+fn decompresses_gzipped_input() {
+    let output = run_on(Path::new(GZIPPED), &[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), plot(&[]));
+}
+
+#[test]
+fn leaves_out_missing_volumes() {
+    let prices = "[1704067200000, 2281.47], [1704153600000, 2352.05]";
+    let volumes = "[1704067200000, null], [1704153600000, null]";
+    let response = format!(
+        r#"{{"prices": [{prices}], "market_caps": [{prices}], "total_volumes": [{volumes}]}}"#
+    );
+    let output = run_on(&save("no-volumes.json", response), &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("leaving out the volume panel"), "{stderr}");
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.contains("Ethereum market cap"));
+    assert!(!svg.contains("Ethereum volume"));
+}
+
+#[test]
+fn embeds_the_svg_in_html() {
+    let html = plot(&["--format", "html"]);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Ethereum in USD</title>"), "{html}");
+    let body = html
+        .split_once("<body>")
+        .and_then(|(_, rest)| rest.split_once("</body>"))
+        .map(|(body, _)| body)
+        .expect("has a body");
+    assert!(body.trim_start().starts_with("<svg"), "{body}");
+    assert!(body.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn reads_the_cache_offline_only_when_fetching() {
+    let output = run(&["--offline"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn shows_the_change_over_the_window() {
+    let svg = plot(&["--show-change"]);
+    assert!(svg.contains("+10.6% over 11d"));
+}
+
+#[test]
+fn mixes_dates_and_times_ago() {
+    // the fixture is of long ago, a day ago is after its end
+    let svg = plot(&["--from", "2024-01-03", "--to", "1d", "--show-change"]);
+    assert!(svg.contains("+14.2% over 9d"));
+}
+
+#[test]
+fn names_what_is_not_a_time_ago() {
+    let output = run(&["--from", "1y6q"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`6q` is not an amount"));
+}
+
+#[test]
+fn exits_with_the_category_of_the_error() {
+    assert_eq!(run(&["--correlation"]).status.code(), Some(2));
+    assert_eq!(run(&["--from", "2030-01-01"]).status.code(), Some(4));
+}
+
+#[test]
+fn leaves_out_the_grid_lines() {
+    // the color of the lines of the grid, faded
+    let grid = "opacity=\"0.1\"";
+    assert!(plot(&[]).contains(grid));
+    let svg = plot(&["--no-grid"]);
+    assert!(!svg.contains(grid));
+    assert!(svg.contains("Ethereum price"));
+}
+
+#[test]
+fn writes_every_output() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("outputs");
+    let (svg, png) = (dir.join("chart.svg"), dir.join("chart.png"));
+    let output = run(&[
+        "--output",
+        svg.to_str().unwrap(),
+        "--output",
+        png.to_str().unwrap(),
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    // and the SVG to stdout
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("<svg"));
+    assert!(fs::read_to_string(&svg).unwrap().contains("Ethereum price"));
+    assert!(fs::read(&png).unwrap().starts_with(b"\x89PNG"));
+    assert!(
+        stderr.contains(&format!("wrote {}", png.display())),
+        "{stderr}"
+    );
+
+    let output = run(&["--output", "-", "--quiet"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn skips_what_is_unchanged() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("unchanged");
+    let _ = fs::remove_dir_all(&dir);
+    let svg = dir.join("chart.svg");
+    let draw = |args: &[&str]| {
+        let output = run(&[
+            &["--output", svg.to_str().unwrap(), "--skip-unchanged"],
+            args,
+        ]
+        .concat());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(output.status.success(), "{stderr}");
+        stderr
+    };
+    assert!(draw(&[]).contains("wrote"));
+    assert!(dir.join("chart.svg.hash").exists());
+    assert!(draw(&[]).contains("unchanged"));
+    // other options draw another chart
+    assert!(draw(&["--sma", "3"]).contains("wrote"));
+    assert!(draw(&["--sma", "3"]).contains("unchanged"));
+}
+
+#[test]
+fn minifies_to_well_formed_svg() {
+    let svg = plot(&[]);
+    let minified = plot(&["--minify"]);
+    assert!(minified.len() < svg.len());
+    assert!(!minified.contains('\n'));
+    let document = roxmltree::Document::parse(&minified).unwrap();
+    let root = document.root_element();
+    assert_eq!(root.tag_name().name(), "svg");
+    assert!(root
+        .descendants()
+        .any(|node| node.text() == Some("Ethereum price")));
+    // as many elements as before
+    let elements = |svg: &str| svg.matches('<').count() - svg.matches("</").count();
+    assert_eq!(elements(&minified), elements(&svg));
+}
+
+#[test]
+fn scales_the_fonts() {
+    let sizes = |svg: &str| -> Vec<f64> {
+        svg.split(" font-size=\"")
+            .skip(1)
+            .map(|rest| rest[..rest.find('"').unwrap()].parse().unwrap())
+            .collect()
+    };
+    let svg = plot(&[]);
+    let scaled = plot(&["--font", "Inter", "--font-scale", "2"]);
+    assert!(!scaled.contains("sans-serif"));
+    assert_eq!(
+        scaled.matches("font-family=\"Inter\"").count(),
+        svg.matches("font-family=\"sans-serif\"").count()
+    );
+    let (sizes, scaled) = (sizes(&svg), sizes(&scaled));
+    assert_eq!(sizes.len(), scaled.len());
+    for (size, scaled) in sizes.iter().zip(scaled) {
+        assert!(
+            (scaled - 2.0 * size).abs() < 1e-9,
+            "{scaled} is not twice {size}"
+        );
+    }
+
+    assert_eq!(run(&["--font-scale", "0"]).status.code(), Some(2));
+}
+
+#[test]
+fn labels_normalized_prices_as_the_change() {
+    let svg = plot(&["--normalize", "--metric", "price"]);
+    assert!(svg.contains("% change"));
+    for label in ["+10%", "0%", "-2%"] {
+        assert!(svg.contains(&format!(">\n{label}\n<")), "no label {label}");
+    }
+    assert!(!plot(&["--metric", "price"]).contains("% change"));
+}
+
+#[test]
+fn draws_the_volume_as_bars() {
+    let rects = |svg: &str| svg.matches("<rect").count();
+    let lines = rects(&plot(&["--metric", "volume"]));
+    let bars = rects(&plot(&["--metric", "volume", "--volume-style", "bar"]));
+    assert_eq!(bars, lines + 12 + 1, "a bar per day and one in the legend");
+
+    // hourly for a bit more than 83 days
+    let input = synthetic(
+        "hourly.json",
+        (0..2000_u32).map(|i| (START + u64::from(i) * HOUR, f64::from(1000 + i % 7))),
+    );
+    let output = run_on(
+        &input,
+        &[
+            "--metric",
+            "volume",
+            "--volume-style",
+            "bar",
+            "--width",
+            "400",
+        ],
+    );
+    assert!(output.status.success());
+    // no more than one bar per 3 pixels
+    let bars = rects(&String::from_utf8(output.stdout).unwrap()) - lines - 1;
+    assert!((100..=133).contains(&bars), "{bars} bars");
+}
+
+#[test]
+fn ticks_long_windows_at_the_first_of_months() {
+    // daily for 200 days from 2024-01-01
+    let input = synthetic(
+        "daily.json",
+        (0..200_u32).map(|i| (START + u64::from(i) * DAY, f64::from(2000 + i % 11))),
+    );
+    let output = run_on(&input, &["--metric", "price"]);
+    assert!(output.status.success());
+    let svg = String::from_utf8(output.stdout).unwrap();
+    let labels: Vec<_> = svg.split("2024-").skip(1).map(|rest| &rest[..5]).collect();
+    assert_eq!(
+        labels,
+        ["01-01", "02-01", "03-01", "04-01", "05-01", "06-01", "07-01"]
+    );
+
+    // shorter windows as before, not on Mondays only
+    let svg = plot(&["--metric", "price"]);
+    assert!(svg.contains("2024-01-03") && svg.contains("2024-01-05"));
+}
+
+#[test]
+fn draws_as_the_command_line_by_default() {
+    let data = eth_hist_plot::parse(File::open(RESPONSE).unwrap()).unwrap();
+    let svg = eth_hist_plot::render_svg(&data, &PlotOptions::default()).unwrap();
+    assert_eq!(svg, plot(&[]));
+
+    let options = PlotOptions {
+        title: Some("Ether, dark".to_owned()),
+        metrics: vec![Metric::Price],
+        theme: Theme::Dark,
+        palette: *Theme::Dark.palette(),
+        ..PlotOptions::default()
+    };
+    let svg = eth_hist_plot::render_svg(&data, &options).unwrap();
+    assert_eq!(
+        svg,
+        plot(&[
+            "--title",
+            "Ether, dark",
+            "--metric",
+            "price",
+            "--theme",
+            "dark"
+        ])
+    );
+}
+
+#[test]
+fn labels_dates_in_the_time_zone() {
+    let annotate = ["--metric", "price", "--annotate", "2024-01-05T20:00:00Z"];
+    assert!(plot(&annotate).contains("2024-01-05 20:00"));
+    let svg = plot(&[&annotate[..], &["--timezone", "Asia/Tokyo"]].concat());
+    assert!(svg.contains("2024-01-06 05:00"));
+
+    let output = run(&["--timezone", "Mars/Olympus"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not an IANA time zone"));
+}
+
+#[test]
+fn ticks_log_scales_at_powers_of_ten() {
+    // daily from 1 to 10,000 over 401 days
+    let input = synthetic(
+        "decades.json",
+        (0..=400_u32).map(|i| {
+            (
+                START + u64::from(i) * DAY,
+                10_f64.powf(f64::from(i) / 100.0),
+            )
+        }),
+    );
+    let output = run_on(&input, &["--metric", "price", "--log-scale"]);
+    assert!(output.status.success());
+    let svg = String::from_utf8(output.stdout).unwrap();
+    let label = |label: &str| svg.contains(&format!(">\n{label}\n<"));
+    for decade in ["1", "10", "100", "1000", "10000"] {
+        assert!(label(decade), "no label {decade}");
+    }
+    assert!(!label("2000") && !label("1000.0"));
+
+    // within less than a decade, as on a linear axis
+    let svg = plot(&["--metric", "price", "--log-scale"]);
+    for label in ["2200", "2400", "2600"] {
+        assert!(svg.contains(&format!(">\n{label}\n<")), "no label {label}");
+    }
+}
+
+#[test]
+fn styles_the_panels_on_their_own() {
+    let svg = plot(&[
+        "--market-cap-color",
+        "#00aa11",
+        "--market-cap-label",
+        "Marktkapitalisierung",
+        "--price-label",
+        "Kurs",
+    ]);
+    for caption in ["Kurs", "Marktkapitalisierung", "Ethereum volume"] {
+        assert!(
+            svg.contains(&format!(">\n{caption}\n<")),
+            "no caption {caption}"
+        );
+    }
+    assert!(!svg.contains(">\nEthereum price\n<"));
+    // the line and its legend, the prices keep the color of the theme
+    assert_eq!(svg.matches("stroke=\"#00AA11\"").count(), 2);
+}